@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS history (
+    id                      INTEGER PRIMARY KEY,
+    preset_id               INTEGER,
+    session_title           TEXT NOT NULL,
+    type                    TEXT NOT NULL CHECK(type IN ('focus', 'break')),
+    planned_duration_in_sec INTEGER NOT NULL,
+    elapsed_in_sec          INTEGER NOT NULL,
+    started_at              TEXT NOT NULL,
+    ended_at                TEXT NOT NULL,
+    outcome                 TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS history_started_at ON history(started_at);
@@ -0,0 +1,29 @@
+-- sessions get an explicit position within their preset, and go away with it
+CREATE TABLE IF NOT EXISTS session_new (
+    id                INTEGER PRIMARY KEY,
+    preset_id         INTEGER NOT NULL,
+    position          INTEGER NOT NULL,
+    name              TEXT NOT NULL,
+    duration_in_sec   INTEGER NOT NULL,
+    color             INTEGER NOT NULL,
+    type              TEXT NOT NULL CHECK(type IN ('focus', 'break')),
+
+    FOREIGN KEY(preset_id) REFERENCES presets(id) ON DELETE CASCADE
+);
+
+INSERT INTO session_new (id, preset_id, position, name, duration_in_sec, color, type)
+SELECT
+    s.id,
+    s.preset_id,
+    (SELECT COUNT(*) FROM session o WHERE o.preset_id = s.preset_id AND o.id < s.id),
+    s.name,
+    s.duration_in_sec,
+    s.color,
+    s.type
+FROM session s
+WHERE s.preset_id IN (SELECT id FROM presets);
+
+DROP TABLE session;
+ALTER TABLE session_new RENAME TO session;
+
+CREATE INDEX IF NOT EXISTS session_preset_position ON session(preset_id, position);
@@ -0,0 +1,13 @@
+-- the preset run in progress, at most one row
+CREATE TABLE IF NOT EXISTS run_state (
+    id                  INTEGER PRIMARY KEY CHECK(id = 1),
+    preset_id           INTEGER NOT NULL,
+    session_index       INTEGER NOT NULL,
+    duration_in_ms      INTEGER NOT NULL,
+    remaining_in_ms     INTEGER NOT NULL,
+    deadline            TEXT, -- only set while running
+    session_started_at  TEXT NOT NULL,
+    updated_at          TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+
+    FOREIGN KEY(preset_id) REFERENCES presets(id) ON DELETE CASCADE
+);
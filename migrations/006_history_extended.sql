@@ -0,0 +1 @@
+ALTER TABLE history ADD COLUMN extended_in_sec INTEGER NOT NULL DEFAULT 0;
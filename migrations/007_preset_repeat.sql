@@ -0,0 +1,5 @@
+-- how many times a preset runs through its sessions, 0 loops until stopped
+ALTER TABLE presets ADD COLUMN repeat_count INTEGER NOT NULL DEFAULT 1;
+
+-- which pass through the preset the saved run is on, counting from 0
+ALTER TABLE run_state ADD COLUMN cycle INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,12 @@
+-- presets described by parameters rather than a hand made session list,
+-- their sessions are still written to `session` whenever the rule is saved
+CREATE TABLE IF NOT EXISTS preset_rule (
+    preset_id            INTEGER PRIMARY KEY,
+    focus_in_sec         INTEGER NOT NULL,
+    short_break_in_sec   INTEGER NOT NULL,
+    long_break_in_sec    INTEGER NOT NULL,
+    long_break_every     INTEGER NOT NULL CHECK(long_break_every > 0),
+    focus_blocks         INTEGER NOT NULL CHECK(focus_blocks > 0),
+
+    FOREIGN KEY(preset_id) REFERENCES presets(id) ON DELETE CASCADE
+);
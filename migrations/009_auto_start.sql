@@ -0,0 +1,7 @@
+-- which sessions start by themselves once the one before ends
+ALTER TABLE presets ADD COLUMN auto_start_focus INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE presets ADD COLUMN auto_start_breaks INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE presets ADD COLUMN auto_start_delay_in_sec INTEGER NOT NULL DEFAULT 0;
+
+-- NULL follows the preset
+ALTER TABLE session ADD COLUMN auto_start INTEGER;
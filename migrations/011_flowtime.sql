@@ -0,0 +1,5 @@
+-- focus sessions that count up until ended by hand
+ALTER TABLE session ADD COLUMN open_ended INTEGER NOT NULL DEFAULT 0;
+
+-- break lengths after an open ended session, "/5" for a ratio or "25=5, 50=8" in minutes
+ALTER TABLE presets ADD COLUMN flow_breaks TEXT NOT NULL DEFAULT '25=5, 50=8, 90=10';
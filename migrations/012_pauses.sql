@@ -0,0 +1,18 @@
+-- limits on pausing a session, NULL means no limit
+ALTER TABLE presets ADD COLUMN max_pauses INTEGER;
+ALTER TABLE presets ADD COLUMN max_paused_in_sec INTEGER;
+ALTER TABLE presets ADD COLUMN abandon_after_in_sec INTEGER;
+
+ALTER TABLE history ADD COLUMN paused_in_sec INTEGER NOT NULL DEFAULT 0;
+
+-- every stretch a session spent paused
+CREATE TABLE IF NOT EXISTS history_pause (
+    id             INTEGER PRIMARY KEY,
+    history_id     INTEGER NOT NULL,
+    started_at     TEXT NOT NULL,
+    length_in_sec  INTEGER NOT NULL,
+
+    FOREIGN KEY(history_id) REFERENCES history(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS history_pause_history_id ON history_pause(history_id);
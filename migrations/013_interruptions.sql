@@ -0,0 +1,12 @@
+-- interruptions logged during a session, per the Pomodoro technique
+CREATE TABLE IF NOT EXISTS history_interruption (
+    id          INTEGER PRIMARY KEY,
+    history_id  INTEGER NOT NULL,
+    kind        TEXT NOT NULL CHECK(kind IN ('internal', 'external')),
+    logged_at   TEXT NOT NULL,
+    note        TEXT,
+
+    FOREIGN KEY(history_id) REFERENCES history(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS history_interruption_history_id ON history_interruption(history_id);
@@ -0,0 +1,6 @@
+-- the daily focus goal from the day it was set, a day without a row
+-- falls under the latest row before it
+CREATE TABLE IF NOT EXISTS daily_goal (
+    day   TEXT PRIMARY KEY,
+    goal  TEXT NOT NULL
+);
@@ -0,0 +1,6 @@
+-- achievements unlocked so far, they stay unlocked even if the history
+-- that earned them is later corrected
+CREATE TABLE IF NOT EXISTS achievement (
+    id           TEXT PRIMARY KEY,
+    unlocked_at  TEXT NOT NULL
+);
@@ -0,0 +1,4 @@
+-- quick timer and stopwatch runs, kept in the history but out of the focus statistics.
+-- rows written before this carry nothing that tells a quick run apart from a preset
+-- session, so they are left as they are and keep counting as focus time
+ALTER TABLE history ADD COLUMN adhoc INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,11 @@
+-- what the saved session carried besides its countdown, so a resumed run picks it all back up
+ALTER TABLE run_state ADD COLUMN extended_in_ms INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE run_state ADD COLUMN overtime_in_ms INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE run_state ADD COLUMN last_flow_in_ms INTEGER;
+
+-- the finished pauses of the saved session, there is only ever one saved run
+CREATE TABLE IF NOT EXISTS run_state_pause (
+    id            INTEGER PRIMARY KEY,
+    started_at    TEXT NOT NULL,
+    length_in_ms  INTEGER NOT NULL
+);
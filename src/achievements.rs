@@ -0,0 +1,387 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::{Days, NaiveDate};
+
+use crate::statistics::{self, DailyGoal, DayFocus};
+
+/// Totals over the whole history that streaks and achievements are judged on
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Progress {
+    pub focus_time: Duration,
+    pub completed: u32, // focus sessions that ran to the end
+    pub current_streak: u32,
+    pub longest_streak: u32,
+    pub goal_days: u32, // days that met the goal set at the time
+}
+
+impl Progress {
+    /// `days` holds the focus per day, oldest first, `goals` the goal history
+    pub fn from_days(
+        days: &[DayFocus],
+        goals: &[(NaiveDate, DailyGoal)],
+        today: NaiveDate,
+    ) -> Self {
+        let mut progress = Progress::default();
+        let mut streak = 0;
+        let mut last_day: Option<NaiveDate> = None;
+        for day in days {
+            progress.focus_time += day.focus;
+            progress.completed += day.completed;
+
+            let goal = statistics::goal_on(day.day, goals);
+            if goal.is_met(day.focus, day.completed) {
+                progress.goal_days += 1;
+            }
+            if !counts_for_streak(day, goal) {
+                streak = 0;
+                continue;
+            }
+
+            let follows = last_day.is_some_and(|last| last + Days::new(1) == day.day);
+            streak = if follows { streak + 1 } else { 1 };
+            last_day = Some(day.day);
+            progress.longest_streak = progress.longest_streak.max(streak);
+        }
+
+        progress.current_streak = current_streak(days, goals, today);
+        return progress;
+    }
+}
+
+/// A day keeps the streak going when it met its goal, or without a goal,
+/// when at least one focus session was completed
+fn counts_for_streak(day: &DayFocus, goal: DailyGoal) -> bool {
+    return match goal {
+        DailyGoal::Off => day.completed > 0,
+        goal => goal.is_met(day.focus, day.completed),
+    };
+}
+
+/// Days in a row up to today that kept the streak going,
+/// today only breaks it once it's over
+fn current_streak(days: &[DayFocus], goals: &[(NaiveDate, DailyGoal)], today: NaiveDate) -> u32 {
+    let by_day: HashMap<NaiveDate, &DayFocus> = days.iter().map(|day| (day.day, day)).collect();
+    let counts = |day: NaiveDate| {
+        by_day
+            .get(&day)
+            .is_some_and(|focus| counts_for_streak(focus, statistics::goal_on(day, goals)))
+    };
+
+    let mut day = if counts(today) {
+        today
+    } else {
+        today - Days::new(1)
+    };
+    let mut streak = 0;
+    while counts(day) {
+        streak += 1;
+        day = day - Days::new(1);
+    }
+
+    return streak;
+}
+
+/// Milestones unlocked once the history reaches them, they stay unlocked after that
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Achievement {
+    FirstSession,
+    HundredSessions,
+    TenHours,
+    HundredHours,
+    FiveHundredHours,
+    FirstGoal,
+    FiftyGoals,
+    ThreeDayStreak,
+    TenDayStreak,
+    ThirtyDayStreak,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 10] = [
+        Achievement::FirstSession,
+        Achievement::HundredSessions,
+        Achievement::TenHours,
+        Achievement::HundredHours,
+        Achievement::FiveHundredHours,
+        Achievement::FirstGoal,
+        Achievement::FiftyGoals,
+        Achievement::ThreeDayStreak,
+        Achievement::TenDayStreak,
+        Achievement::ThirtyDayStreak,
+    ];
+
+    /// The id stored in the `achievement` table
+    pub fn as_str(&self) -> &'static str {
+        return match self {
+            Achievement::FirstSession => "first_session",
+            Achievement::HundredSessions => "sessions_100",
+            Achievement::TenHours => "hours_10",
+            Achievement::HundredHours => "hours_100",
+            Achievement::FiveHundredHours => "hours_500",
+            Achievement::FirstGoal => "first_goal",
+            Achievement::FiftyGoals => "goals_50",
+            Achievement::ThreeDayStreak => "streak_3",
+            Achievement::TenDayStreak => "streak_10",
+            Achievement::ThirtyDayStreak => "streak_30",
+        };
+    }
+
+    pub fn from_str(value: &str) -> Option<Self> {
+        return Achievement::ALL
+            .into_iter()
+            .find(|achievement| achievement.as_str() == value);
+    }
+
+    pub fn title(&self) -> &'static str {
+        return match self {
+            Achievement::FirstSession => "First focus",
+            Achievement::HundredSessions => "Centurion",
+            Achievement::TenHours => "Warming up",
+            Achievement::HundredHours => "Deep worker",
+            Achievement::FiveHundredHours => "Craftsperson",
+            Achievement::FirstGoal => "On target",
+            Achievement::FiftyGoals => "Reliable",
+            Achievement::ThreeDayStreak => "Getting started",
+            Achievement::TenDayStreak => "On a roll",
+            Achievement::ThirtyDayStreak => "Unstoppable",
+        };
+    }
+
+    pub fn description(&self) -> &'static str {
+        return match self {
+            Achievement::FirstSession => "Complete a focus session",
+            Achievement::HundredSessions => "Complete 100 focus sessions",
+            Achievement::TenHours => "Focus for 10 hours",
+            Achievement::HundredHours => "Focus for 100 hours",
+            Achievement::FiveHundredHours => "Focus for 500 hours",
+            Achievement::FirstGoal => "Meet your daily goal",
+            Achievement::FiftyGoals => "Meet your daily goal on 50 days",
+            Achievement::ThreeDayStreak => "Keep a 3 day streak",
+            Achievement::TenDayStreak => "Keep a 10 day streak",
+            Achievement::ThirtyDayStreak => "Keep a 30 day streak",
+        };
+    }
+
+    /// How far along the way to the achievement the history is, as (done, target)
+    pub fn progress(&self, progress: &Progress) -> (u32, u32) {
+        let hours = (progress.focus_time.as_secs() / 3600) as u32;
+        return match self {
+            Achievement::FirstSession => (progress.completed, 1),
+            Achievement::HundredSessions => (progress.completed, 100),
+            Achievement::TenHours => (hours, 10),
+            Achievement::HundredHours => (hours, 100),
+            Achievement::FiveHundredHours => (hours, 500),
+            Achievement::FirstGoal => (progress.goal_days, 1),
+            Achievement::FiftyGoals => (progress.goal_days, 50),
+            Achievement::ThreeDayStreak => (progress.longest_streak, 3),
+            Achievement::TenDayStreak => (progress.longest_streak, 10),
+            Achievement::ThirtyDayStreak => (progress.longest_streak, 30),
+        };
+    }
+
+    pub fn is_reached(&self, progress: &Progress) -> bool {
+        let (done, target) = self.progress(progress);
+        return done >= target;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIN: Duration = Duration::from_secs(60);
+
+    fn date(day: u32) -> NaiveDate {
+        return NaiveDate::from_ymd_opt(2026, 3, day).unwrap();
+    }
+
+    /// `day` of March with `minutes` of focus over `completed` finished sessions
+    fn focus(day: u32, minutes: u32, completed: u32) -> DayFocus {
+        return DayFocus {
+            day: date(day),
+            focus: MIN * minutes,
+            sessions: completed,
+            completed,
+        };
+    }
+
+    fn progress_on(days: &[DayFocus], goals: &[(NaiveDate, DailyGoal)], today: u32) -> Progress {
+        let progress = Progress::from_days(days, goals, date(today));
+        assert!(progress.longest_streak >= progress.current_streak);
+        return progress;
+    }
+
+    #[test]
+    fn a_missed_day_breaks_the_streak() {
+        let days = [
+            focus(1, 25, 1),
+            focus(2, 25, 1),
+            focus(3, 25, 1),
+            focus(5, 25, 1),
+            focus(6, 25, 1),
+        ];
+
+        let progress = progress_on(&days, &[], 6);
+        assert_eq!(progress.current_streak, 2);
+        assert_eq!(progress.longest_streak, 3);
+
+        // a day with focus but nothing completed doesn't count either
+        let days = [focus(1, 25, 1), focus(2, 20, 0), focus(3, 25, 1)];
+        let progress = progress_on(&days, &[], 3);
+        assert_eq!(progress.current_streak, 1);
+        assert_eq!(progress.longest_streak, 1);
+    }
+
+    #[test]
+    fn with_a_goal_one_completed_session_is_not_enough() {
+        let goals = [(date(1), DailyGoal::Minutes(60))];
+        let days = [focus(1, 60, 2), focus(2, 25, 1), focus(3, 75, 3)];
+
+        let progress = progress_on(&days, &goals, 3);
+        assert_eq!(progress.goal_days, 2);
+        assert_eq!(progress.current_streak, 1);
+        assert_eq!(progress.longest_streak, 1);
+        assert_eq!(progress.completed, 6);
+        assert_eq!(progress.focus_time, 160 * MIN);
+
+        // without a goal every one of those days counts
+        let progress = progress_on(&days, &[], 3);
+        assert_eq!(progress.goal_days, 0);
+        assert_eq!(progress.current_streak, 3);
+    }
+
+    #[test]
+    fn today_only_breaks_the_streak_once_it_is_over() {
+        let days = [focus(4, 25, 1), focus(5, 25, 1)];
+        assert_eq!(progress_on(&days, &[], 6).current_streak, 2);
+
+        let days = [focus(4, 25, 1), focus(5, 25, 1), focus(6, 10, 0)];
+        assert_eq!(progress_on(&days, &[], 6).current_streak, 2);
+
+        let days = [focus(4, 25, 1), focus(5, 25, 1), focus(6, 25, 1)];
+        assert_eq!(progress_on(&days, &[], 6).current_streak, 3);
+
+        // yesterday missed as well, the streak is gone
+        let days = [focus(4, 25, 1)];
+        assert_eq!(progress_on(&days, &[], 6).current_streak, 0);
+    }
+
+    #[test]
+    fn a_goal_change_applies_from_its_day() {
+        let goals = [(date(1), DailyGoal::Off), (date(3), DailyGoal::Sessions(2))];
+        let days = [
+            focus(1, 25, 1),
+            focus(2, 25, 1),
+            focus(3, 25, 1),
+            focus(4, 50, 2),
+        ];
+
+        let progress = progress_on(&days, &goals, 4);
+        assert_eq!(progress.longest_streak, 2);
+        assert_eq!(progress.current_streak, 1);
+        assert_eq!(progress.goal_days, 1);
+    }
+
+    #[test]
+    fn achievements_unlock_at_their_thresholds() {
+        let cases = [
+            (
+                Achievement::FirstSession,
+                Progress {
+                    completed: 1,
+                    ..Default::default()
+                },
+            ),
+            (
+                Achievement::HundredSessions,
+                Progress {
+                    completed: 100,
+                    ..Default::default()
+                },
+            ),
+            (
+                Achievement::TenHours,
+                Progress {
+                    focus_time: 600 * MIN,
+                    ..Default::default()
+                },
+            ),
+            (
+                Achievement::HundredHours,
+                Progress {
+                    focus_time: 6000 * MIN,
+                    ..Default::default()
+                },
+            ),
+            (
+                Achievement::FiveHundredHours,
+                Progress {
+                    focus_time: 30000 * MIN,
+                    ..Default::default()
+                },
+            ),
+            (
+                Achievement::FirstGoal,
+                Progress {
+                    goal_days: 1,
+                    ..Default::default()
+                },
+            ),
+            (
+                Achievement::FiftyGoals,
+                Progress {
+                    goal_days: 50,
+                    ..Default::default()
+                },
+            ),
+            (
+                Achievement::ThreeDayStreak,
+                Progress {
+                    longest_streak: 3,
+                    ..Default::default()
+                },
+            ),
+            (
+                Achievement::TenDayStreak,
+                Progress {
+                    longest_streak: 10,
+                    ..Default::default()
+                },
+            ),
+            (
+                Achievement::ThirtyDayStreak,
+                Progress {
+                    longest_streak: 30,
+                    ..Default::default()
+                },
+            ),
+        ];
+
+        for (achievement, reached) in cases {
+            assert!(achievement.is_reached(&reached), "{achievement:?}");
+
+            // one short of the target, a minute short for the hours
+            let short = Progress {
+                focus_time: reached.focus_time.saturating_sub(MIN),
+                completed: reached.completed.saturating_sub(1),
+                current_streak: reached.longest_streak.saturating_sub(1),
+                longest_streak: reached.longest_streak.saturating_sub(1),
+                goal_days: reached.goal_days.saturating_sub(1),
+            };
+            assert!(!achievement.is_reached(&short), "{achievement:?}");
+            assert_eq!(
+                Achievement::from_str(achievement.as_str()),
+                Some(achievement)
+            );
+        }
+
+        // streaks are judged on the longest one, not the one going now
+        let broken = Progress {
+            longest_streak: 3,
+            current_streak: 0,
+            ..Default::default()
+        };
+        assert!(Achievement::ThreeDayStreak.is_reached(&broken));
+    }
+}
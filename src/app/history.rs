@@ -0,0 +1,746 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Days, Local, NaiveDateTime, Utc};
+use gpui::prelude::FluentBuilder;
+use gpui::{
+    App, AppContext, Context, Div, Entity, EventEmitter, ParentElement, Render, SharedString,
+    Styled, Window, div,
+};
+use gpui_component::{
+    ActiveTheme as _, Icon, IndexPath, Sizable, TitleBar,
+    button::{Button, ButtonVariants},
+    input::{Input, InputState},
+    label::Label,
+    list::{ListDelegate, ListEvent, ListItem, ListState},
+};
+
+use crate::db::Database;
+use crate::duration;
+use crate::events::history::HistoryChangedEvent;
+use crate::events::navigation::{NavigationEvent, Screen};
+use crate::history::{HistoryEntry, HistoryFilter, SessionOutcome};
+use crate::session::SessionKind;
+use crate::statistics;
+
+/// Entries loaded at most, the filters narrow it down further back
+const HISTORY_LIMIT: u32 = 500;
+
+/// How `started_at` is typed in the entry form, in local time
+const STARTED_AT_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DateRange {
+    Today,
+    Week,
+    Month,
+    Year,
+    All,
+}
+
+impl DateRange {
+    fn label(&self) -> &'static str {
+        return match self {
+            DateRange::Today => "Today",
+            DateRange::Week => "7 days",
+            DateRange::Month => "30 days",
+            DateRange::Year => "Year",
+            DateRange::All => "All",
+        };
+    }
+
+    /// Where the range starts, None for no limit
+    fn start(&self) -> Option<DateTime<Utc>> {
+        let today = Local::now().date_naive();
+        let days = match self {
+            DateRange::Today => 0,
+            DateRange::Week => 6,
+            DateRange::Month => 29,
+            DateRange::Year => 364,
+            DateRange::All => return None,
+        };
+        return Some(statistics::start_of_day(today - Days::new(days)));
+    }
+}
+
+/// Picking the next value of a filter, None (anything) comes after the last one
+fn next_in<T: Copy + PartialEq>(options: &[T], current: Option<T>) -> Option<T> {
+    return match current {
+        None => options.first().copied(),
+        Some(value) => options
+            .iter()
+            .position(|option| *option == value)
+            .and_then(|index| options.get(index + 1))
+            .copied(),
+    };
+}
+
+fn kind_label(kind: SessionKind) -> &'static str {
+    return match kind {
+        SessionKind::WORK => "Focus",
+        SessionKind::BREAK => "Break",
+    };
+}
+
+fn outcome_label(outcome: SessionOutcome) -> &'static str {
+    return match outcome {
+        SessionOutcome::Completed => "Completed",
+        SessionOutcome::Stopped => "Stopped",
+        SessionOutcome::Skipped => "Skipped",
+        SessionOutcome::Abandoned => "Abandoned",
+    };
+}
+
+const OUTCOMES: [SessionOutcome; 4] = [
+    SessionOutcome::Completed,
+    SessionOutcome::Stopped,
+    SessionOutcome::Skipped,
+    SessionOutcome::Abandoned,
+];
+
+/// The entry being corrected, or added by hand when it has no id yet
+struct EntryForm {
+    entry: HistoryEntry,
+    title: Entity<InputState>,
+    started_at: Entity<InputState>,
+    planned: Entity<InputState>, // read by `duration::parse`
+    elapsed: Entity<InputState>, // read by `duration::parse`
+    confirming_delete: bool,
+}
+
+impl EntryForm {
+    fn new(entry: HistoryEntry, window: &mut Window, cx: &mut Context<HistoryScreen>) -> Self {
+        let title = entry.session_title.clone();
+        let started_at = entry
+            .started_at
+            .with_timezone(&Local)
+            .format(STARTED_AT_FORMAT)
+            .to_string();
+        let planned = duration::format_short(entry.planned);
+        let elapsed = duration::format_short(entry.elapsed);
+
+        let mut input = |placeholder: &'static str, value: String| {
+            cx.new(|cx| {
+                InputState::new(window, cx)
+                    .placeholder(placeholder)
+                    .default_value(value)
+            })
+        };
+
+        return Self {
+            title: input("Session title", title.to_string()),
+            started_at: input("YYYY-MM-DD HH:MM", started_at),
+            planned: input("e.g. 25m", planned),
+            elapsed: input("e.g. 25m", elapsed),
+            confirming_delete: false,
+            entry,
+        };
+    }
+
+    /// The entry with the form's values, ended_at follows from the start and time spent
+    fn read(&self, cx: &App) -> Result<HistoryEntry, SharedString> {
+        let title = self.title.read(cx).value().trim().to_string();
+        if title.is_empty() {
+            return Err("The entry needs a title".into());
+        }
+
+        let started_at = NaiveDateTime::parse_from_str(
+            self.started_at.read(cx).value().trim(),
+            STARTED_AT_FORMAT,
+        )
+        .ok()
+        .and_then(|at| at.and_local_timezone(Local).earliest())
+        .map(|at| at.with_timezone(&Utc))
+        .ok_or_else(|| SharedString::from("Started needs to look like 2025-01-31 09:30"))?;
+
+        let length = |input: &Entity<InputState>, name: &str| {
+            duration::parse(&input.read(cx).value())
+                .ok_or_else(|| SharedString::from(format!("{name} needs a duration like 25m")))
+        };
+        let planned = length(&self.planned, "Planned")?;
+        let elapsed = length(&self.elapsed, "Actual")?;
+        if elapsed.is_zero() {
+            return Err("Actual needs to be longer than zero".into());
+        }
+
+        let mut entry = self.entry.clone();
+        entry.session_title = title.into();
+        entry.started_at = started_at;
+        entry.planned = planned;
+        entry.elapsed = elapsed;
+        entry.ended_at = started_at
+            + chrono::Duration::from_std(elapsed + entry.paused)
+                .unwrap_or(chrono::Duration::zero());
+        return Ok(entry);
+    }
+}
+
+/// Past session runs, filtered and open to corrections
+pub struct HistoryScreen {
+    list: Entity<ListState<HistoryListDelegate>>,
+    preset_titles: Vec<(i64, SharedString)>, // deleted presets too, their entries are still listed
+    range: DateRange,
+    preset_id: Option<i64>,
+    kind: Option<SessionKind>,
+    outcome: Option<SessionOutcome>,
+    form: Option<EntryForm>,
+    error: Option<SharedString>,
+    is_saving: bool,
+}
+
+impl EventEmitter<NavigationEvent> for HistoryScreen {}
+impl EventEmitter<HistoryChangedEvent> for HistoryScreen {}
+
+impl HistoryScreen {
+    pub fn new(cx: &mut Context<Self>, window: &mut Window) -> Self {
+        let list = cx.new(|cx| {
+            ListState::new(
+                HistoryListDelegate {
+                    items: vec![],
+                    selected_index: None,
+                },
+                window,
+                cx,
+            )
+        });
+
+        // open the picked entry in the form
+        cx.subscribe_in(
+            &list,
+            window,
+            |this, list, event: &ListEvent, window, cx| {
+                if let ListEvent::Confirm(ix) = event {
+                    let entry = list
+                        .read(cx)
+                        .delegate()
+                        .items
+                        .get(ix.row)
+                        .map(|i| i.entry.clone());
+                    if let Some(entry) = entry {
+                        this.open_form(entry, window, cx);
+                    }
+                }
+            },
+        )
+        .detach();
+
+        return Self {
+            list,
+            preset_titles: vec![],
+            range: DateRange::Month,
+            preset_id: None,
+            kind: None,
+            outcome: None,
+            form: None,
+            error: None,
+            is_saving: false,
+        };
+    }
+
+    /// Load the preset titles again, then the entries with them
+    pub fn reload_presets(&mut self, cx: &mut Context<Self>) {
+        let presets = cx.global::<Database>().presets();
+        cx.spawn(async move |this, cx| -> anyhow::Result<()> {
+            let titles = presets.titles().await?;
+            this.update(cx, |screen, cx| {
+                screen.preset_titles = titles
+                    .into_iter()
+                    .map(|(id, title)| (id, title.into()))
+                    .collect();
+                screen.reload(cx);
+            })?;
+            return Ok(());
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn preset_title(&self, preset_id: Option<i64>) -> SharedString {
+        return preset_id
+            .and_then(|id| self.preset_titles.iter().find(|(p, _)| *p == id))
+            .map(|(_, title)| title.clone())
+            .unwrap_or_else(|| "No preset".into());
+    }
+
+    /// Load the entries matching the filters again
+    pub fn reload(&mut self, cx: &mut Context<Self>) {
+        let filter = HistoryFilter {
+            from: self.range.start(),
+            to: None,
+            preset_id: self.preset_id,
+            kind: self.kind,
+            outcome: self.outcome,
+        };
+
+        let history = cx.global::<Database>().history();
+        cx.spawn(async move |this, cx| -> anyhow::Result<()> {
+            let entries = history.list(&filter, HISTORY_LIMIT).await?;
+            this.update(cx, |screen, cx| screen.set_entries(entries, cx))?;
+            return Ok(());
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn set_entries(&mut self, entries: Vec<HistoryEntry>, cx: &mut Context<Self>) {
+        let items = entries
+            .into_iter()
+            .map(|entry| HistoryItem {
+                preset_title: self.preset_title(entry.preset_id),
+                entry,
+            })
+            .collect();
+
+        self.list.update(cx, |list, cx| {
+            let delegate = list.delegate_mut();
+            delegate.items = items;
+            delegate.selected_index = None;
+            cx.notify();
+        });
+    }
+
+    fn open_form(&mut self, entry: HistoryEntry, window: &mut Window, cx: &mut Context<Self>) {
+        self.form = Some(EntryForm::new(entry, window, cx));
+        self.error = None;
+        cx.notify();
+    }
+
+    /// A completed focus session that ended just now, for focus done away from the computer
+    fn add_entry(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let elapsed = Duration::from_secs(25 * 60);
+        let ended_at = Utc::now();
+        let started_at =
+            ended_at - chrono::Duration::from_std(elapsed).unwrap_or(chrono::Duration::zero());
+
+        let entry = HistoryEntry {
+            id: None,
+            preset_id: self.preset_id,
+            session_title: "Focus".into(),
+            kind: SessionKind::WORK,
+            planned: elapsed,
+            extended: Duration::ZERO,
+            elapsed,
+            overtime: Duration::ZERO,
+            paused: Duration::ZERO,
+            pauses: vec![],
+            interruptions: vec![],
+            started_at,
+            ended_at,
+            outcome: SessionOutcome::Completed,
+            adhoc: false,
+        };
+        self.open_form(entry, window, cx);
+    }
+
+    fn close_form(&mut self, cx: &mut Context<Self>) {
+        self.form = None;
+        self.error = None;
+        self.is_saving = false;
+        cx.notify();
+    }
+
+    fn save_form(&mut self, cx: &mut Context<Self>) {
+        let Some(form) = &self.form else {
+            return;
+        };
+        if self.is_saving {
+            return;
+        }
+
+        let entry = match form.read(cx) {
+            Ok(entry) => entry,
+            Err(error) => {
+                self.error = Some(error);
+                cx.notify();
+                return;
+            }
+        };
+
+        self.is_saving = true;
+        self.error = None;
+        cx.notify();
+
+        let history = cx.global::<Database>().history();
+        cx.spawn(async move |this, cx| {
+            let result = match entry.id {
+                Some(_) => history.update(&entry).await,
+                None => history.record(&entry).await.map(|_| ()),
+            };
+
+            let _ = this.update(cx, |screen, cx| match result {
+                Ok(()) => {
+                    screen.close_form(cx);
+                    screen.reload(cx);
+                    cx.emit(HistoryChangedEvent);
+                }
+                Err(error) => {
+                    screen.is_saving = false;
+                    screen.error = Some(format!("Could not save the entry: {error}").into());
+                    cx.notify();
+                }
+            });
+        })
+        .detach();
+    }
+
+    fn delete_entry(&mut self, cx: &mut Context<Self>) {
+        let Some(id) = self.form.as_ref().and_then(|form| form.entry.id) else {
+            return;
+        };
+
+        let history = cx.global::<Database>().history();
+        cx.spawn(async move |this, cx| -> anyhow::Result<()> {
+            history.delete(id).await?;
+            this.update(cx, |screen, cx| {
+                screen.close_form(cx);
+                screen.reload(cx);
+                cx.emit(HistoryChangedEvent);
+            })?;
+            return Ok(());
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn set_confirming_delete(&mut self, confirming: bool, cx: &mut Context<Self>) {
+        if let Some(form) = self.form.as_mut() {
+            form.confirming_delete = confirming;
+        }
+        cx.notify();
+    }
+
+    fn confirm_delete(&self, cx: &mut Context<Self>) -> Div {
+        return div()
+            .flex()
+            .flex_row()
+            .items_center()
+            .justify_between()
+            .gap_2()
+            .child("Delete this entry? This can't be undone.")
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .gap_2()
+                    .child(
+                        Button::new("history-delete-confirm")
+                            .label("Delete")
+                            .danger()
+                            .on_click(cx.listener(|this, _event, _window, cx| {
+                                this.delete_entry(cx);
+                            })),
+                    )
+                    .child(
+                        Button::new("history-delete-cancel")
+                            .label("Keep")
+                            .ghost()
+                            .on_click(cx.listener(|this, _event, _window, cx| {
+                                this.set_confirming_delete(false, cx);
+                            })),
+                    ),
+            );
+    }
+
+    fn set_filter(&mut self, change: impl FnOnce(&mut Self), cx: &mut Context<Self>) {
+        change(self);
+        self.reload(cx);
+        cx.notify();
+    }
+
+    fn filters(&self, cx: &mut Context<Self>) -> Div {
+        let ranges = [
+            DateRange::Today,
+            DateRange::Week,
+            DateRange::Month,
+            DateRange::Year,
+            DateRange::All,
+        ];
+        let preset_label = match self.preset_id {
+            Some(id) => self.preset_title(Some(id)),
+            None => "All presets".into(),
+        };
+        let kind_label = self.kind.map(kind_label).unwrap_or("Any kind");
+        let outcome_label = self.outcome.map(outcome_label).unwrap_or("Any outcome");
+
+        return div()
+            .flex()
+            .flex_col()
+            .gap_2()
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .gap_1()
+                    .children(ranges.into_iter().map(|range| {
+                        Button::new(range.label())
+                            .label(range.label())
+                            .small()
+                            .when_else(range == self.range, |b| b.primary(), |b| b.ghost())
+                            .on_click(cx.listener(move |this, _event, _window, cx| {
+                                this.set_filter(|this| this.range = range, cx);
+                            }))
+                    })),
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .gap_1()
+                    .child(
+                        Button::new("history-filter-preset")
+                            .label(preset_label)
+                            .small()
+                            .ghost()
+                            .on_click(cx.listener(|this, _event, _window, cx| {
+                                let ids: Vec<i64> =
+                                    this.preset_titles.iter().map(|(id, _)| *id).collect();
+                                let next = next_in(&ids, this.preset_id);
+                                this.set_filter(|this| this.preset_id = next, cx);
+                            })),
+                    )
+                    .child(
+                        Button::new("history-filter-kind")
+                            .label(kind_label)
+                            .small()
+                            .ghost()
+                            .on_click(cx.listener(|this, _event, _window, cx| {
+                                let next =
+                                    next_in(&[SessionKind::WORK, SessionKind::BREAK], this.kind);
+                                this.set_filter(|this| this.kind = next, cx);
+                            })),
+                    )
+                    .child(
+                        Button::new("history-filter-outcome")
+                            .label(outcome_label)
+                            .small()
+                            .ghost()
+                            .on_click(cx.listener(|this, _event, _window, cx| {
+                                let next = next_in(&OUTCOMES, this.outcome);
+                                this.set_filter(|this| this.outcome = next, cx);
+                            })),
+                    ),
+            );
+    }
+
+    fn form_view(&self, form: &EntryForm, cx: &mut Context<Self>) -> Div {
+        let field = |label: &'static str, input: &Entity<InputState>| {
+            div()
+                .flex()
+                .flex_row()
+                .items_center()
+                .justify_between()
+                .gap_2()
+                .child(Label::new(label))
+                .child(div().w_48().child(Input::new(input)))
+        };
+        let entry = &form.entry;
+        let is_new = entry.id.is_none();
+
+        return div()
+            .flex()
+            .flex_col()
+            .gap_2()
+            .child(Label::new(if is_new {
+                "Add an entry"
+            } else {
+                "Edit entry"
+            }))
+            .child(field("Title", &form.title))
+            .child(field("Started", &form.started_at))
+            .child(field("Planned", &form.planned))
+            .child(field("Actual", &form.elapsed))
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .gap_2()
+                    .child(
+                        Button::new("history-form-kind")
+                            .label(kind_label(entry.kind))
+                            .ghost()
+                            .on_click(cx.listener(|this, _event, _window, cx| {
+                                if let Some(form) = this.form.as_mut() {
+                                    form.entry.kind = match form.entry.kind {
+                                        SessionKind::WORK => SessionKind::BREAK,
+                                        SessionKind::BREAK => SessionKind::WORK,
+                                    };
+                                }
+                                cx.notify();
+                            })),
+                    )
+                    .child(
+                        Button::new("history-form-outcome")
+                            .label(outcome_label(entry.outcome))
+                            .ghost()
+                            .on_click(cx.listener(|this, _event, _window, cx| {
+                                if let Some(form) = this.form.as_mut() {
+                                    form.entry.outcome =
+                                        next_in(&OUTCOMES, Some(form.entry.outcome))
+                                            .unwrap_or(OUTCOMES[0]);
+                                }
+                                cx.notify();
+                            })),
+                    ),
+            )
+            .when_some(self.error.clone(), |el, error| {
+                el.child(div().text_sm().text_color(cx.theme().danger).child(error))
+            })
+            .when(form.confirming_delete, |el| {
+                el.child(self.confirm_delete(cx))
+            })
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .justify_end()
+                    .gap_2()
+                    .when(!is_new && !form.confirming_delete, |el| {
+                        el.child(
+                            Button::new("history-form-delete")
+                                .label("Delete")
+                                .danger()
+                                .on_click(cx.listener(|this, _event, _window, cx| {
+                                    this.set_confirming_delete(true, cx);
+                                })),
+                        )
+                    })
+                    .child(
+                        Button::new("history-form-cancel")
+                            .label("Cancel")
+                            .ghost()
+                            .on_click(cx.listener(|this, _event, _window, cx| {
+                                this.close_form(cx);
+                            })),
+                    )
+                    .child(
+                        Button::new("history-form-save")
+                            .label("Save")
+                            .primary()
+                            .loading(self.is_saving)
+                            .on_click(cx.listener(|this, _event, _window, cx| {
+                                this.save_form(cx);
+                            })),
+                    ),
+            );
+    }
+}
+
+impl Render for HistoryScreen {
+    fn render(
+        &mut self,
+        _window: &mut gpui::Window,
+        cx: &mut gpui::Context<Self>,
+    ) -> impl gpui::IntoElement {
+        let body = match &self.form {
+            Some(form) => self.form_view(form, cx),
+            None => div()
+                .flex()
+                .flex_col()
+                .flex_grow()
+                .gap_2()
+                .child(self.filters(cx))
+                .child(div().flex_grow().child(self.list.clone())),
+        };
+
+        return div()
+            .size_full()
+            .flex()
+            .flex_col()
+            .child(
+                TitleBar::new().child(div().child("History")).child(
+                    div()
+                        .flex()
+                        .items_center()
+                        .gap_2()
+                        .when(self.form.is_none(), |el| {
+                            el.child(
+                                Button::new("history-add")
+                                    .label("Add entry")
+                                    .ghost()
+                                    .on_click(cx.listener(|this, _event, window, cx| {
+                                        this.add_entry(window, cx);
+                                    })),
+                            )
+                        })
+                        .child(
+                            Button::new("history-close")
+                                .icon(Icon::new(Icon::empty()).path("icons/x.svg"))
+                                .ghost()
+                                .on_click(cx.listener(|_this, _event, _window, cx| {
+                                    cx.emit(NavigationEvent {
+                                        screen: Screen::Statistics,
+                                    });
+                                })),
+                        ),
+                ),
+            )
+            .child(div().p_2().flex().flex_col().flex_grow().child(body));
+    }
+}
+
+struct HistoryItem {
+    entry: HistoryEntry,
+    preset_title: SharedString,
+}
+
+struct HistoryListDelegate {
+    items: Vec<HistoryItem>,
+    selected_index: Option<IndexPath>,
+}
+
+impl ListDelegate for HistoryListDelegate {
+    type Item = ListItem;
+
+    fn items_count(&self, _section: usize, _cx: &App) -> usize {
+        self.items.len()
+    }
+
+    fn render_item(&self, ix: IndexPath, _window: &mut Window, cx: &mut App) -> Option<Self::Item> {
+        self.items.get(ix.row).map(|item| {
+            let entry = &item.entry;
+            let started_at = entry
+                .started_at
+                .with_timezone(&Local)
+                .format("%a %-d %b, %H:%M");
+            ListItem::new(ix)
+                .child(
+                    div()
+                        .flex()
+                        .flex_col()
+                        .child(
+                            div()
+                                .flex()
+                                .flex_row()
+                                .justify_between()
+                                .child(format!(
+                                    "{} ・ {}",
+                                    kind_label(entry.kind),
+                                    entry.session_title
+                                ))
+                                .child(outcome_label(entry.outcome)),
+                        )
+                        .child(
+                            div()
+                                .flex()
+                                .flex_row()
+                                .justify_between()
+                                .text_sm()
+                                .text_color(cx.theme().muted_foreground)
+                                .child(format!("{started_at} ・ {}", item.preset_title))
+                                .child(format!(
+                                    "{} of {}",
+                                    duration::format_minutes(entry.elapsed),
+                                    duration::format_minutes(entry.planned + entry.extended)
+                                )),
+                        ),
+                )
+                .selected(Some(ix) == self.selected_index)
+        })
+    }
+
+    fn set_selected_index(
+        &mut self,
+        ix: Option<IndexPath>,
+        _window: &mut Window,
+        cx: &mut Context<ListState<Self>>,
+    ) {
+        self.selected_index = ix;
+        cx.notify();
+    }
+}
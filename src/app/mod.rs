@@ -1,21 +1,49 @@
+use chrono::{DateTime, Days, Local, Utc};
 use gpui::{AppContext, Context, Entity, ParentElement, Render, Styled, Window, div};
+use gpui_component::notification::Notification;
+use gpui_component::{Root, WindowExt};
 
+use crate::achievements::{Achievement, Progress};
+
+use crate::app::history::HistoryScreen;
+use crate::app::preset_edit::PresetEditScreen;
+use crate::app::statistics::StatisticsScreen;
+use crate::app::summary::SummaryScreen;
 use crate::app::timer::TimerScreen;
+use crate::db::Database;
+use crate::events::history::HistoryChangedEvent;
 use crate::events::navigation::{NavigationEvent, Screen};
+use crate::events::preset::{DeletePresetEvent, EditPresetEvent, PresetSavedEvent, UsePresetEvent};
+use crate::events::summary::{PresetFinishedEvent, SummaryActionEvent};
+use crate::session::TimerPreset;
+use crate::statistics::start_of_day;
 
+mod history;
+mod preset_edit;
 mod settings;
+mod statistics;
+mod summary;
 mod timer;
 
 pub struct BmoApp {
     current_screen: Screen,
+    presets: Vec<TimerPreset>,
     timer_screen: Entity<TimerScreen>,
     setting_screen: Entity<settings::SettingScreen>,
+    preset_edit_screen: Entity<PresetEditScreen>,
+    summary_screen: Entity<SummaryScreen>,
+    statistics_screen: Entity<StatisticsScreen>,
+    history_screen: Entity<HistoryScreen>,
 }
 
 impl BmoApp {
     pub fn new(cx: &mut Context<Self>, window: &mut Window) -> Self {
-        let timer_screen = cx.new(|cx| TimerScreen::new(cx));
+        let timer_screen = cx.new(|cx| TimerScreen::new(cx, window));
         let setting_screen = cx.new(|cx| settings::SettingScreen::new(cx, window));
+        let preset_edit_screen = cx.new(|cx| PresetEditScreen::new(cx, window));
+        let summary_screen = cx.new(|_| SummaryScreen::new());
+        let statistics_screen = cx.new(StatisticsScreen::new);
+        let history_screen = cx.new(|cx| HistoryScreen::new(cx, window));
 
         // When we click settings on the timer app, show the settings page
         cx.subscribe(
@@ -34,14 +62,316 @@ impl BmoApp {
         )
         .detach();
 
-        return Self {
+        cx.subscribe(
+            &preset_edit_screen,
+            |parent, _entity, event: &NavigationEvent, context| {
+                parent.set_screen(event.screen, context);
+            },
+        )
+        .detach();
+
+        cx.subscribe(
+            &statistics_screen,
+            |parent, _entity, event: &NavigationEvent, context| {
+                parent.set_screen(event.screen, context);
+            },
+        )
+        .detach();
+
+        cx.subscribe(
+            &history_screen,
+            |parent, _entity, event: &NavigationEvent, context| {
+                parent.set_screen(event.screen, context);
+            },
+        )
+        .detach();
+
+        // open the editor with the picked preset (or a blank one)
+        cx.subscribe_in(
+            &setting_screen,
+            window,
+            |parent, _entity, event: &EditPresetEvent, window, cx| {
+                let preset = event
+                    .preset_id
+                    .and_then(|id| parent.presets.iter().find(|p| p.id == Some(id)))
+                    .cloned();
+
+                parent
+                    .preset_edit_screen
+                    .update(cx, |screen, cx| screen.edit(preset, window, cx));
+                parent.set_screen(Screen::PresetEdit, cx);
+            },
+        )
+        .detach();
+
+        // run the picked preset on the timer
+        cx.subscribe(
+            &setting_screen,
+            |parent, _entity, event: &UsePresetEvent, cx| {
+                let Some(preset) = parent
+                    .presets
+                    .iter()
+                    .find(|p| p.id == Some(event.preset_id))
+                else {
+                    return;
+                };
+
+                let preset = preset.clone();
+                parent
+                    .timer_screen
+                    .update(cx, |screen, cx| screen.set_preset(preset, cx));
+                parent.set_screen(Screen::Timer, cx);
+            },
+        )
+        .detach();
+
+        cx.subscribe(
+            &setting_screen,
+            |parent, _entity, event: &DeletePresetEvent, cx| {
+                parent.delete_preset(event.preset_id, cx);
+            },
+        )
+        .detach();
+
+        cx.subscribe(
+            &preset_edit_screen,
+            |parent, _entity, _event: &PresetSavedEvent, cx| {
+                parent.reload_presets(cx);
+            },
+        )
+        .detach();
+
+        // show the summary once a preset runs to the end
+        cx.subscribe(
+            &timer_screen,
+            |parent, _entity, event: &PresetFinishedEvent, cx| {
+                let event = event.clone();
+                parent.summary_screen.update(cx, |screen, cx| {
+                    screen.show(event.preset, event.summary, cx)
+                });
+                parent.set_screen(Screen::Summary, cx);
+            },
+        )
+        .detach();
+
+        cx.subscribe(
+            &summary_screen,
+            |parent, _entity, event: &SummaryActionEvent, cx| {
+                parent.handle_summary_action(*event, cx);
+            },
+        )
+        .detach();
+
+        // a new or corrected entry may unlock an achievement
+        cx.subscribe_in(
+            &timer_screen,
+            window,
+            |parent, _entity, _event: &HistoryChangedEvent, window, cx| {
+                parent.check_achievements(window, cx);
+            },
+        )
+        .detach();
+
+        cx.subscribe_in(
+            &history_screen,
+            window,
+            |parent, _entity, _event: &HistoryChangedEvent, window, cx| {
+                parent.check_achievements(window, cx);
+            },
+        )
+        .detach();
+
+        let mut app = Self {
             current_screen: Screen::Timer,
+            presets: vec![],
             timer_screen,
             setting_screen,
+            preset_edit_screen,
+            summary_screen,
+            statistics_screen,
+            history_screen,
         };
+
+        app.reload_presets(cx);
+        app.load_saved_run(cx);
+        app.check_achievements(window, cx);
+        return app;
+    }
+
+    /// Unlock the achievements the history has reached and let the user know about each new one,
+    /// the ones an existing history already earned on the first check come as a single notice
+    fn check_achievements(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let database = cx.global::<Database>();
+        let history = database.history();
+        let goal_repository = database.goals();
+        let achievement_repository = database.achievements();
+
+        cx.spawn_in(window, async move |_this, cx| -> anyhow::Result<()> {
+            let today = Local::now().date_naive();
+            let days = history
+                .focus_per_day(
+                    DateTime::<Utc>::UNIX_EPOCH,
+                    start_of_day(today + Days::new(1)),
+                )
+                .await?;
+            let goals = goal_repository.list().await?;
+            let progress = Progress::from_days(&days, &goals, today);
+            let first_check = achievement_repository.list().await?.is_empty();
+
+            let mut unlocked = vec![];
+            for achievement in Achievement::ALL {
+                if achievement.is_reached(&progress)
+                    && achievement_repository
+                        .unlock(achievement, Utc::now())
+                        .await?
+                {
+                    unlocked.push(achievement);
+                }
+            }
+
+            cx.update(|window, cx| {
+                if first_check && unlocked.len() > 1 {
+                    let titles: Vec<&str> = unlocked.iter().map(|a| a.title()).collect();
+                    window.push_notification(
+                        Notification::new()
+                            .title(format!("{} achievements unlocked", unlocked.len()))
+                            .message(format!("From your past sessions: {}", titles.join(", "))),
+                        cx,
+                    );
+                    return;
+                }
+
+                for achievement in unlocked {
+                    window.push_notification(
+                        Notification::new()
+                            .title(format!("Achievement unlocked: {}", achievement.title()))
+                            .message(achievement.description()),
+                        cx,
+                    );
+                }
+            })?;
+            return Ok(());
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Offer to resume the run that was going on when the app last closed
+    fn load_saved_run(&mut self, cx: &mut Context<Self>) {
+        let database = cx.global::<Database>();
+        let preset_repository = database.presets();
+        let run_state_repository = database.run_state();
+
+        cx.spawn(async move |this, cx| -> anyhow::Result<()> {
+            let Some(saved) = run_state_repository.load().await? else {
+                return Ok(());
+            };
+
+            let Some(preset) = preset_repository.get(saved.preset_id).await? else {
+                run_state_repository.clear().await?;
+                return Ok(());
+            };
+
+            this.update(cx, |parent, cx| {
+                parent
+                    .timer_screen
+                    .update(cx, |screen, cx| screen.offer_resume(preset, saved, cx));
+            })?;
+            return Ok(());
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Load the saved presets, the screens keep what they have until this resolves
+    fn reload_presets(&mut self, cx: &mut Context<Self>) {
+        let preset_repository = cx.global::<Database>().presets();
+        cx.spawn(async move |this, cx| -> anyhow::Result<()> {
+            let presets = preset_repository.list_or_seed().await?;
+            this.update(cx, |parent, cx| parent.set_presets(presets, cx))?;
+            return Ok(());
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Soft delete a preset, the timer moves on to another one if it was using it
+    fn delete_preset(&mut self, preset_id: i64, cx: &mut Context<Self>) {
+        let preset_repository = cx.global::<Database>().presets();
+        cx.spawn(async move |this, cx| -> anyhow::Result<()> {
+            preset_repository.delete(preset_id).await?;
+            this.update(cx, |parent, cx| parent.reload_presets(cx))?;
+            return Ok(());
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn set_presets(&mut self, presets: Vec<TimerPreset>, cx: &mut Context<Self>) {
+        // keep the timer on the preset it was using, if it still exists
+        let active_id = self.timer_screen.read(cx).preset_id(cx);
+        let active = presets
+            .iter()
+            .find(|p| active_id.is_some() && p.id == active_id);
+
+        if let Some(preset) = active.cloned() {
+            self.timer_screen
+                .update(cx, |screen, cx| screen.refresh_preset(preset, cx));
+        } else if let Some(preset) = presets.first().cloned() {
+            self.timer_screen
+                .update(cx, |screen, cx| screen.set_preset(preset, cx));
+        }
+
+        self.setting_screen
+            .update(cx, |screen, cx| screen.set_presets(&presets, cx));
+        self.summary_screen
+            .update(cx, |screen, cx| screen.set_presets(&presets, cx));
+        self.history_screen
+            .update(cx, |screen, cx| screen.reload_presets(cx));
+        self.presets = presets;
+    }
+
+    fn handle_summary_action(&mut self, action: SummaryActionEvent, cx: &mut Context<Self>) {
+        match action {
+            SummaryActionEvent::RepeatPreset => {
+                self.timer_screen
+                    .update(cx, |screen, cx| screen.start_timer(cx));
+            }
+            SummaryActionEvent::StartPreset(preset_id) => {
+                let Some(preset) = self.presets.iter().find(|p| p.id == Some(preset_id)) else {
+                    return;
+                };
+
+                let preset = preset.clone();
+                self.timer_screen.update(cx, |screen, cx| {
+                    screen.set_preset(preset, cx);
+                    screen.start_timer(cx);
+                });
+            }
+            SummaryActionEvent::FinishForToday => {
+                self.timer_screen
+                    .update(cx, |screen, cx| screen.reset_preset(cx));
+            }
+        }
+
+        self.set_screen(Screen::Timer, cx);
     }
 
     fn set_screen(&mut self, screen: Screen, cx: &mut Context<Self>) {
+        // the history may have grown since the statistics were last looked at
+        match screen {
+            Screen::Statistics => {
+                self.statistics_screen
+                    .update(cx, |screen, cx| screen.reload(cx));
+            }
+            Screen::History => {
+                self.history_screen
+                    .update(cx, |screen, cx| screen.reload(cx));
+            }
+            // entries may have been corrected or added in the history browser
+            Screen::Timer => {
+                self.timer_screen
+                    .update(cx, |screen, cx| screen.reload_today_focus(cx));
+            }
+            _ => {}
+        }
+
         self.current_screen = screen;
         cx.notify();
     }
@@ -50,12 +380,19 @@ impl BmoApp {
 impl Render for BmoApp {
     fn render(
         &mut self,
-        _window: &mut gpui::Window,
-        _cx: &mut gpui::Context<Self>,
+        window: &mut gpui::Window,
+        cx: &mut gpui::Context<Self>,
     ) -> impl gpui::IntoElement {
-        return match self.current_screen {
+        let screen = match self.current_screen {
             Screen::Timer => div().size_full().child(self.timer_screen.clone()),
             Screen::Settings => div().size_full().child(self.setting_screen.clone()),
+            Screen::PresetEdit => div().size_full().child(self.preset_edit_screen.clone()),
+            Screen::Summary => div().size_full().child(self.summary_screen.clone()),
+            Screen::Statistics => div().size_full().child(self.statistics_screen.clone()),
+            Screen::History => div().size_full().child(self.history_screen.clone()),
         };
+
+        // achievement notifications show on top of whichever screen is open
+        return screen.children(Root::render_notification_layer(window, cx));
     }
 }
@@ -0,0 +1,909 @@
+use std::time::Duration;
+
+use gpui::prelude::FluentBuilder;
+use gpui::{
+    AppContext, Context, Div, Entity, EventEmitter, InteractiveElement, ParentElement, Render,
+    SharedString, StatefulInteractiveElement, Styled, Window, div, rgb,
+};
+use gpui_component::{
+    ActiveTheme as _, Disableable, Icon, IconName, TitleBar,
+    button::{Button, ButtonVariants},
+    checkbox::Checkbox,
+    input::{Input, InputState},
+    label::Label,
+};
+
+use crate::db::Database;
+use crate::duration;
+use crate::events::navigation::{NavigationEvent, Screen};
+use crate::events::preset::PresetSavedEvent;
+use crate::session::{
+    AutoStart, FlowBreaks, PauseLimits, PresetRepeat, PresetRule, SESSION_COLORS, Session,
+    SessionKind, TimerPreset,
+};
+
+struct SessionRow {
+    title: Entity<InputState>,
+    duration: Entity<InputState>, // read by `duration::parse`
+    kind: SessionKind,
+    color: u32,
+    auto_start: Option<bool>, // None follows the preset
+    open_ended: bool,
+}
+
+impl SessionRow {
+    fn new(session: &Session, window: &mut Window, cx: &mut Context<PresetEditScreen>) -> Self {
+        let title = session.title.clone();
+        let length = duration::format_short(session.duration);
+
+        return Self {
+            title: cx.new(|cx| {
+                InputState::new(window, cx)
+                    .placeholder("Session title")
+                    .default_value(title)
+            }),
+            duration: cx.new(|cx| {
+                InputState::new(window, cx)
+                    .placeholder("e.g. 25m")
+                    .default_value(length)
+            }),
+            kind: session.kind,
+            color: session.color,
+            auto_start: session.auto_start,
+            open_ended: session.open_ended,
+        };
+    }
+}
+
+/// The parameters of a rule based preset
+struct RuleInputs {
+    focus: Entity<InputState>,
+    short_break: Entity<InputState>,
+    long_break: Entity<InputState>,
+    long_break_every: Entity<InputState>,
+    focus_blocks: Entity<InputState>,
+}
+
+impl RuleInputs {
+    fn new(rule: &PresetRule, window: &mut Window, cx: &mut Context<PresetEditScreen>) -> Self {
+        let mut input = |placeholder: &'static str, value: String| {
+            cx.new(|cx| {
+                InputState::new(window, cx)
+                    .placeholder(placeholder)
+                    .default_value(value)
+            })
+        };
+
+        return Self {
+            focus: input("e.g. 50m", duration::format_short(rule.focus)),
+            short_break: input("e.g. 10m", duration::format_short(rule.short_break)),
+            long_break: input("e.g. 20m", duration::format_short(rule.long_break)),
+            long_break_every: input("Blocks", rule.long_break_every.to_string()),
+            focus_blocks: input("Blocks", rule.focus_blocks.to_string()),
+        };
+    }
+
+    fn read(&self, cx: &Context<PresetEditScreen>) -> Result<PresetRule, SharedString> {
+        let number = |input: &Entity<InputState>, name: &str| match input
+            .read(cx)
+            .value()
+            .trim()
+            .parse::<u32>()
+        {
+            Ok(value) if value > 0 => Ok(value),
+            _ => Err(SharedString::from(format!(
+                "{name} needs a whole number above 0"
+            ))),
+        };
+        let length = |input: &Entity<InputState>, name: &str| {
+            duration::parse(&input.read(cx).value())
+                .filter(|length| !length.is_zero())
+                .ok_or_else(|| {
+                    SharedString::from(format!("{name} needs a duration like 25m or 1h30m"))
+                })
+        };
+
+        return Ok(PresetRule {
+            focus: length(&self.focus, "Focus")?,
+            short_break: length(&self.short_break, "Short break")?,
+            long_break: length(&self.long_break, "Long break")?,
+            long_break_every: number(&self.long_break_every, "Long break every")?,
+            focus_blocks: number(&self.focus_blocks, "Focus blocks")?,
+        });
+    }
+}
+
+pub struct PresetEditScreen {
+    preset_id: Option<i64>,
+    name: Entity<InputState>,
+    description: Entity<InputState>,
+    repeat: Entity<InputState>, // times to run through the sessions
+    loop_forever: bool,
+    auto_start_focus: bool,
+    auto_start_breaks: bool,
+    auto_start_delay: Entity<InputState>, // in seconds
+    flow_breaks: Entity<InputState>,
+    max_pauses: Entity<InputState>, // empty for no limit, as are the two below
+    max_paused: Entity<InputState>, // read by `duration::parse`
+    abandon_after: Entity<InputState>, // read by `duration::parse`
+    rule: Option<RuleInputs>,       // set while the preset is edited as a rule instead of a list
+    sessions: Vec<SessionRow>,
+    error: Option<SharedString>,
+    is_saving: bool,
+}
+
+impl EventEmitter<NavigationEvent> for PresetEditScreen {}
+impl EventEmitter<PresetSavedEvent> for PresetEditScreen {}
+
+impl PresetEditScreen {
+    pub fn new(cx: &mut Context<Self>, window: &mut Window) -> Self {
+        let name = cx.new(|cx| InputState::new(window, cx).placeholder("Preset name"));
+        let description = cx.new(|cx| InputState::new(window, cx).placeholder("Description"));
+        let repeat = cx.new(|cx| InputState::new(window, cx).placeholder("Times"));
+        let auto_start_delay = cx.new(|cx| InputState::new(window, cx).placeholder("Seconds"));
+        let flow_breaks = cx.new(|cx| InputState::new(window, cx).placeholder("25=5, 50=8 or /5"));
+        let max_pauses = cx.new(|cx| InputState::new(window, cx).placeholder("No limit"));
+        let max_paused = cx.new(|cx| InputState::new(window, cx).placeholder("No limit"));
+        let abandon_after = cx.new(|cx| InputState::new(window, cx).placeholder("Never"));
+
+        return Self {
+            preset_id: None,
+            name,
+            description,
+            repeat,
+            loop_forever: false,
+            auto_start_focus: true,
+            auto_start_breaks: true,
+            auto_start_delay,
+            flow_breaks,
+            max_pauses,
+            max_paused,
+            abandon_after,
+            rule: None,
+            sessions: vec![],
+            error: None,
+            is_saving: false,
+        };
+    }
+
+    /// Load `preset` into the editor, or an empty preset when creating a new one
+    pub fn edit(
+        &mut self,
+        preset: Option<TimerPreset>,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let preset = preset.unwrap_or_else(|| TimerPreset {
+            id: None,
+            title: "".into(),
+            description: None,
+            sessions: vec![],
+            repeat: PresetRepeat::default(),
+            rule: Some(PresetRule::default()),
+            auto_start: AutoStart::default(),
+            flow_breaks: FlowBreaks::default(),
+            pause_limits: PauseLimits::default(),
+        });
+
+        self.preset_id = preset.id;
+        self.error = None;
+        self.is_saving = false;
+
+        let title = preset.title.clone();
+        let description = preset.description.clone().unwrap_or_default();
+        self.name
+            .update(cx, |input, cx| input.set_value(title, window, cx));
+        self.description
+            .update(cx, |input, cx| input.set_value(description, window, cx));
+
+        // looping presets keep the count field at 1 in case looping is switched off
+        let (times, loop_forever) = match preset.repeat {
+            PresetRepeat::Times(times) => (times, false),
+            PresetRepeat::Forever => (1, true),
+        };
+        self.loop_forever = loop_forever;
+        self.repeat.update(cx, |input, cx| {
+            input.set_value(times.to_string(), window, cx)
+        });
+
+        self.auto_start_focus = preset.auto_start.focus;
+        self.auto_start_breaks = preset.auto_start.breaks;
+        let delay = preset.auto_start.delay.as_secs().to_string();
+        self.auto_start_delay
+            .update(cx, |input, cx| input.set_value(delay, window, cx));
+        let flow_breaks = preset.flow_breaks.to_text();
+        self.flow_breaks
+            .update(cx, |input, cx| input.set_value(flow_breaks, window, cx));
+
+        let limits = preset.pause_limits;
+        let max_pauses = limits
+            .max_pauses
+            .map(|count| count.to_string())
+            .unwrap_or_default();
+        let max_paused = limits
+            .max_paused
+            .map(duration::format_short)
+            .unwrap_or_default();
+        let abandon_after = limits
+            .abandon_after
+            .map(duration::format_short)
+            .unwrap_or_default();
+        self.max_pauses
+            .update(cx, |input, cx| input.set_value(max_pauses, window, cx));
+        self.max_paused
+            .update(cx, |input, cx| input.set_value(max_paused, window, cx));
+        self.abandon_after
+            .update(cx, |input, cx| input.set_value(abandon_after, window, cx));
+
+        self.rule = preset.rule.map(|rule| RuleInputs::new(&rule, window, cx));
+        self.sessions = preset
+            .sessions
+            .iter()
+            .map(|session| SessionRow::new(session, window, cx))
+            .collect();
+
+        cx.notify();
+    }
+
+    fn use_rule(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.rule.is_none() {
+            self.rule = Some(RuleInputs::new(&PresetRule::default(), window, cx));
+            cx.notify();
+        }
+    }
+
+    /// Switch to editing the sessions one by one, starting from what the rule expands to.
+    /// A rule that doesn't read stays as it is so the mistake can be fixed
+    fn use_session_list(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let Some(inputs) = &self.rule else {
+            return;
+        };
+
+        let rule = match inputs.read(cx) {
+            Ok(rule) => rule,
+            Err(error) => {
+                self.error = Some(error);
+                cx.notify();
+                return;
+            }
+        };
+
+        self.rule = None;
+        self.error = None;
+        self.sessions = rule
+            .expand()
+            .iter()
+            .map(|session| SessionRow::new(session, window, cx))
+            .collect();
+        cx.notify();
+    }
+
+    fn add_session(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        // alternate focus and break so the common case needs the fewest clicks
+        let kind = match self.sessions.last().map(|row| row.kind) {
+            Some(SessionKind::WORK) => SessionKind::BREAK,
+            _ => SessionKind::WORK,
+        };
+        let (title, minutes) = match kind {
+            SessionKind::WORK => ("Focus", 25),
+            SessionKind::BREAK => ("Short break", 5),
+        };
+
+        let session = Session::new(title.into(), Duration::from_secs(minutes * 60), kind);
+        self.sessions.push(SessionRow::new(&session, window, cx));
+        cx.notify();
+    }
+
+    fn remove_session(&mut self, index: usize, cx: &mut Context<Self>) {
+        if index < self.sessions.len() {
+            self.sessions.remove(index);
+            cx.notify();
+        }
+    }
+
+    /// Move the session at `index` one step up (`-1`) or down (`1`)
+    fn move_session(&mut self, index: usize, offset: isize, cx: &mut Context<Self>) {
+        let Some(target) = index.checked_add_signed(offset) else {
+            return;
+        };
+
+        if target < self.sessions.len() {
+            self.sessions.swap(index, target);
+            cx.notify();
+        }
+    }
+
+    /// Go round following the preset, always auto starting and never auto starting
+    fn cycle_auto_start(&mut self, index: usize, cx: &mut Context<Self>) {
+        let Some(row) = self.sessions.get_mut(index) else {
+            return;
+        };
+
+        row.auto_start = match row.auto_start {
+            None => Some(true),
+            Some(true) => Some(false),
+            Some(false) => None,
+        };
+        cx.notify();
+    }
+
+    /// Switch a focus session between a set duration and counting up until ended by hand
+    fn toggle_open_ended(&mut self, index: usize, cx: &mut Context<Self>) {
+        let Some(row) = self.sessions.get_mut(index) else {
+            return;
+        };
+
+        row.open_ended = row.kind == SessionKind::WORK && !row.open_ended;
+        cx.notify();
+    }
+
+    fn toggle_kind(&mut self, index: usize, cx: &mut Context<Self>) {
+        let Some(row) = self.sessions.get_mut(index) else {
+            return;
+        };
+
+        let new_kind = match row.kind {
+            SessionKind::WORK => SessionKind::BREAK,
+            SessionKind::BREAK => SessionKind::WORK,
+        };
+
+        // keep custom colors, only follow the kind if the color was never changed
+        if row.color == Session::default_color(&row.kind) {
+            row.color = Session::default_color(&new_kind);
+        }
+        row.kind = new_kind;
+        // only focus sessions can be open ended
+        row.open_ended = row.open_ended && new_kind == SessionKind::WORK;
+        cx.notify();
+    }
+
+    fn cycle_color(&mut self, index: usize, cx: &mut Context<Self>) {
+        let Some(row) = self.sessions.get_mut(index) else {
+            return;
+        };
+
+        let next = SESSION_COLORS
+            .iter()
+            .position(|c| *c == row.color)
+            .map(|i| (i + 1) % SESSION_COLORS.len())
+            .unwrap_or(0);
+        row.color = SESSION_COLORS[next];
+        cx.notify();
+    }
+
+    /// Validate the form and turn it into a preset
+    fn build_preset(&self, cx: &Context<Self>) -> Result<TimerPreset, SharedString> {
+        let title = self.name.read(cx).value().trim().to_string();
+        if title.is_empty() {
+            return Err("The preset needs a name".into());
+        }
+
+        let repeat = if self.loop_forever {
+            PresetRepeat::Forever
+        } else {
+            match self.repeat.read(cx).value().trim().parse::<u32>() {
+                Ok(times) if times > 0 => PresetRepeat::Times(times),
+                _ => return Err("Repeat needs a whole number of times".into()),
+            }
+        };
+
+        let delay = match self.auto_start_delay.read(cx).value().trim() {
+            "" => 0,
+            value => value
+                .parse::<u64>()
+                .map_err(|_| SharedString::from("The countdown needs a whole number of seconds"))?,
+        };
+        let flow_breaks =
+            FlowBreaks::parse(&self.flow_breaks.read(cx).value()).ok_or_else(|| {
+                SharedString::from(
+                    "Flow breaks need to look like \"25=5, 50=8\" or \"/5\", with breaks of a minute or more",
+                )
+            })?;
+        let pause_limits = self.build_pause_limits(cx)?;
+        let auto_start = AutoStart {
+            focus: self.auto_start_focus,
+            breaks: self.auto_start_breaks,
+            delay: Duration::from_secs(delay),
+        };
+
+        let (sessions, rule) = match &self.rule {
+            Some(inputs) => {
+                let rule = inputs.read(cx)?;
+                (rule.expand(), Some(rule))
+            }
+            None => (self.build_sessions(cx)?, None),
+        };
+
+        let description = self.description.read(cx).value().trim().to_string();
+        return Ok(TimerPreset {
+            id: self.preset_id,
+            title: title.into(),
+            description: (!description.is_empty()).then(|| description.into()),
+            sessions,
+            repeat,
+            rule,
+            auto_start,
+            flow_breaks,
+            pause_limits,
+        });
+    }
+
+    /// Read the pause limit fields, an empty one sets no limit
+    fn build_pause_limits(&self, cx: &Context<Self>) -> Result<PauseLimits, SharedString> {
+        let max_pauses = match self.max_pauses.read(cx).value().trim() {
+            "" => None,
+            value => Some(value.parse::<u32>().map_err(|_| {
+                SharedString::from("Max pauses needs a whole number, or nothing for no limit")
+            })?),
+        };
+        let length = |input: &Entity<InputState>, name: &str| {
+            let value = input.read(cx).value();
+            if value.trim().is_empty() {
+                return Ok(None);
+            }
+            return duration::parse(&value)
+                .filter(|length| !length.is_zero())
+                .map(Some)
+                .ok_or_else(|| SharedString::from(format!("{name} needs a duration like 10m")));
+        };
+
+        return Ok(PauseLimits {
+            max_pauses,
+            max_paused: length(&self.max_paused, "Max pause time")?,
+            abandon_after: length(&self.abandon_after, "Abandon after")?,
+        });
+    }
+
+    fn build_sessions(&self, cx: &Context<Self>) -> Result<Vec<Session>, SharedString> {
+        if self.sessions.is_empty() {
+            return Err("Add at least one session".into());
+        }
+
+        let mut sessions = Vec::with_capacity(self.sessions.len());
+        for (index, row) in self.sessions.iter().enumerate() {
+            let session_title = row.title.read(cx).value().trim().to_string();
+            if session_title.is_empty() {
+                return Err(format!("Session {} needs a title", index + 1).into());
+            }
+
+            // open ended sessions count up, whatever duration they had is ignored
+            let length = match duration::parse(&row.duration.read(cx).value()) {
+                Some(length) if !length.is_zero() => length,
+                _ if row.open_ended => Duration::ZERO,
+                _ => {
+                    return Err(format!(
+                        "Session {} needs a duration like 25m, 1h30m or 1:30:00",
+                        index + 1
+                    )
+                    .into());
+                }
+            };
+
+            let mut session = Session::new(session_title.into(), length, row.kind);
+            session.color = row.color;
+            session.auto_start = row.auto_start;
+            session.open_ended = row.open_ended;
+            sessions.push(session);
+        }
+
+        return Ok(sessions);
+    }
+
+    fn save(&mut self, cx: &mut Context<Self>) {
+        if self.is_saving {
+            return;
+        }
+
+        let preset = match self.build_preset(cx) {
+            Ok(preset) => preset,
+            Err(error) => {
+                self.error = Some(error);
+                cx.notify();
+                return;
+            }
+        };
+
+        self.error = None;
+        self.is_saving = true;
+        cx.notify();
+
+        let repository = cx.global::<Database>().presets();
+        cx.spawn(async move |this, cx| -> anyhow::Result<()> {
+            let result = match preset.id {
+                Some(_) => repository.update(&preset).await,
+                None => repository.create(&preset).await.map(|_| ()),
+            };
+
+            this.update(cx, |this, cx| {
+                this.is_saving = false;
+                match result {
+                    Ok(()) => {
+                        cx.emit(PresetSavedEvent);
+                        cx.emit(NavigationEvent {
+                            screen: Screen::Settings,
+                        });
+                    }
+                    Err(err) => {
+                        this.error = Some(format!("Could not save the preset: {err}").into());
+                    }
+                }
+                cx.notify();
+            })?;
+
+            return Ok(());
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn session_row(&self, index: usize, row: &SessionRow, cx: &mut Context<Self>) -> Div {
+        let kind_label = match row.kind {
+            SessionKind::WORK => "Focus",
+            SessionKind::BREAK => "Break",
+        };
+        let auto_start_label = match row.auto_start {
+            None => "Default",
+            Some(true) => "Auto",
+            Some(false) => "Manual",
+        };
+
+        return div()
+            .flex()
+            .flex_row()
+            .items_center()
+            .gap_2()
+            .child(
+                div()
+                    .id(("session-color", index))
+                    .size_6()
+                    .flex_none()
+                    .rounded_full()
+                    .border_1()
+                    .border_color(cx.theme().border)
+                    .bg(rgb(row.color))
+                    .cursor_pointer()
+                    .on_click(cx.listener(move |this, _event, _window, cx| {
+                        this.cycle_color(index, cx);
+                    })),
+            )
+            .child(div().flex_grow().child(Input::new(&row.title)))
+            .child(
+                div()
+                    .w_20()
+                    .when(row.open_ended, |el| el.opacity(0.5))
+                    .child(Input::new(&row.duration).disabled(row.open_ended)),
+            )
+            .child(
+                Button::new(("session-kind", index))
+                    .label(kind_label)
+                    .ghost()
+                    .on_click(cx.listener(move |this, _event, _window, cx| {
+                        this.toggle_kind(index, cx);
+                    })),
+            )
+            .when(row.kind == SessionKind::WORK, |el| {
+                el.child(
+                    Button::new(("session-open-ended", index))
+                        .label(if row.open_ended { "Flow" } else { "Timed" })
+                        .ghost()
+                        .on_click(cx.listener(move |this, _event, _window, cx| {
+                            this.toggle_open_ended(index, cx);
+                        })),
+                )
+            })
+            .child(
+                Button::new(("session-auto-start", index))
+                    .label(auto_start_label)
+                    .ghost()
+                    .on_click(cx.listener(move |this, _event, _window, cx| {
+                        this.cycle_auto_start(index, cx);
+                    })),
+            )
+            .child(
+                Button::new(("session-up", index))
+                    .icon(IconName::ChevronUp)
+                    .ghost()
+                    .disabled(index == 0)
+                    .on_click(cx.listener(move |this, _event, _window, cx| {
+                        this.move_session(index, -1, cx);
+                    })),
+            )
+            .child(
+                Button::new(("session-down", index))
+                    .icon(IconName::ChevronDown)
+                    .ghost()
+                    .disabled(index + 1 == self.sessions.len())
+                    .on_click(cx.listener(move |this, _event, _window, cx| {
+                        this.move_session(index, 1, cx);
+                    })),
+            )
+            .child(
+                Button::new(("session-remove", index))
+                    .icon(Icon::new(Icon::empty()).path("icons/x.svg"))
+                    .ghost()
+                    .on_click(cx.listener(move |this, _event, _window, cx| {
+                        this.remove_session(index, cx);
+                    })),
+            );
+    }
+
+    fn repeat_row(&self, cx: &mut Context<Self>) -> Div {
+        return div()
+            .flex()
+            .flex_row()
+            .items_center()
+            .gap_2()
+            .child(Label::new("Repeat"))
+            .child(
+                div()
+                    .w_20()
+                    .when(self.loop_forever, |el| el.opacity(0.5))
+                    .child(Input::new(&self.repeat).disabled(self.loop_forever)),
+            )
+            .child(Label::new("times"))
+            .child(
+                Checkbox::new("preset-loop-forever")
+                    .label("Loop until stopped")
+                    .checked(self.loop_forever)
+                    .on_click(cx.listener(|this, checked: &bool, _window, cx| {
+                        this.loop_forever = *checked;
+                        cx.notify();
+                    })),
+            );
+    }
+
+    fn auto_start_row(&self, cx: &mut Context<Self>) -> Div {
+        return div()
+            .flex()
+            .flex_col()
+            .gap_2()
+            .child(
+                Checkbox::new("preset-auto-start-focus")
+                    .label("Start focus sessions automatically")
+                    .checked(self.auto_start_focus)
+                    .on_click(cx.listener(|this, checked: &bool, _window, cx| {
+                        this.auto_start_focus = *checked;
+                        cx.notify();
+                    })),
+            )
+            .child(
+                Checkbox::new("preset-auto-start-breaks")
+                    .label("Start breaks automatically")
+                    .checked(self.auto_start_breaks)
+                    .on_click(cx.listener(|this, checked: &bool, _window, cx| {
+                        this.auto_start_breaks = *checked;
+                        cx.notify();
+                    })),
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .gap_2()
+                    .child(Label::new("Countdown before starting"))
+                    .child(div().w_20().child(Input::new(&self.auto_start_delay)))
+                    .child(Label::new("seconds")),
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .gap_2()
+                    .child(Label::new("Breaks after flow focus (minutes)"))
+                    .child(div().flex_grow().child(Input::new(&self.flow_breaks))),
+            );
+    }
+
+    fn pause_limits_row(&self) -> Div {
+        let field = |label: &'static str, input: &Entity<InputState>| {
+            div()
+                .flex()
+                .flex_row()
+                .items_center()
+                .justify_between()
+                .gap_2()
+                .child(Label::new(label))
+                .child(div().w_24().child(Input::new(input)))
+        };
+
+        return div()
+            .flex()
+            .flex_col()
+            .gap_2()
+            .child(field("Pauses per session", &self.max_pauses))
+            .child(field("Time paused per session", &self.max_paused))
+            .child(field(
+                "Abandon focus paused for longer than",
+                &self.abandon_after,
+            ));
+    }
+
+    /// Switch between describing the preset as a rule or as a list of sessions
+    fn mode_row(&self, cx: &mut Context<Self>) -> Div {
+        let is_rule = self.rule.is_some();
+        return div()
+            .flex()
+            .flex_row()
+            .gap_2()
+            .child(
+                Button::new("preset-mode-rule")
+                    .label("Rule")
+                    .when_else(is_rule, |b| b.primary(), |b| b.ghost())
+                    .on_click(cx.listener(|this, _event, window, cx| {
+                        this.use_rule(window, cx);
+                    })),
+            )
+            .child(
+                Button::new("preset-mode-list")
+                    .label("Session list")
+                    .when_else(is_rule, |b| b.ghost(), |b| b.primary())
+                    .on_click(cx.listener(|this, _event, window, cx| {
+                        this.use_session_list(window, cx);
+                    })),
+            );
+    }
+
+    fn rule_fields(&self, rule: &RuleInputs) -> Div {
+        let field = |label: &'static str, input: &Entity<InputState>| {
+            div()
+                .flex()
+                .flex_row()
+                .items_center()
+                .justify_between()
+                .gap_2()
+                .child(Label::new(label))
+                .child(div().w_20().child(Input::new(input)))
+        };
+
+        return div()
+            .flex()
+            .flex_col()
+            .gap_2()
+            .child(field("Focus", &rule.focus))
+            .child(field("Short break", &rule.short_break))
+            .child(field("Long break", &rule.long_break))
+            .child(field(
+                "Long break every (focus blocks)",
+                &rule.long_break_every,
+            ))
+            .child(field("Focus blocks", &rule.focus_blocks));
+    }
+
+    fn session_list(&self, cx: &mut Context<Self>) -> Div {
+        let rows: Vec<Div> = self
+            .sessions
+            .iter()
+            .enumerate()
+            .map(|(index, row)| self.session_row(index, row, cx))
+            .collect();
+
+        return div()
+            .flex()
+            .flex_col()
+            .gap_2()
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .justify_between()
+                    .items_center()
+                    .child(Label::new("Sessions"))
+                    .child(
+                        Button::new("add-session-button")
+                            .icon(IconName::Plus)
+                            .on_click(cx.listener(|this, _event, window, cx| {
+                                this.add_session(window, cx);
+                            })),
+                    ),
+            )
+            .children(rows);
+    }
+
+    fn body(&self, cx: &mut Context<Self>) -> impl gpui::IntoElement {
+        let sessions = match &self.rule {
+            Some(rule) => self.rule_fields(rule),
+            None => self.session_list(cx),
+        };
+
+        return div()
+            .id("preset-edit-body")
+            .p_2()
+            .flex()
+            .flex_col()
+            .gap_4()
+            .overflow_y_scroll()
+            .child(
+                div()
+                    .flex()
+                    .flex_col()
+                    .gap_2()
+                    .child(Label::new("Name"))
+                    .child(Input::new(&self.name))
+                    .child(Label::new("Description"))
+                    .child(Input::new(&self.description)),
+            )
+            .child(self.repeat_row(cx))
+            .child(self.auto_start_row(cx))
+            .child(self.pause_limits_row())
+            .child(self.mode_row(cx))
+            .child(sessions);
+    }
+
+    fn footer(&self, cx: &mut Context<Self>) -> Div {
+        return div()
+            .p_2()
+            .flex()
+            .flex_row()
+            .items_center()
+            .justify_between()
+            .gap_2()
+            .child(
+                div()
+                    .text_sm()
+                    .text_color(cx.theme().danger)
+                    .when_some(self.error.clone(), |el, error| el.child(error)),
+            )
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .gap_2()
+                    .child(
+                        Button::new("cancel-preset-button")
+                            .label("Cancel")
+                            .ghost()
+                            .on_click(cx.listener(|_this, _event, _window, cx| {
+                                cx.emit(NavigationEvent {
+                                    screen: Screen::Settings,
+                                });
+                            })),
+                    )
+                    .child(
+                        Button::new("save-preset-button")
+                            .label("Save")
+                            .primary()
+                            .loading(self.is_saving)
+                            .on_click(cx.listener(|this, _event, _window, cx| {
+                                this.save(cx);
+                            })),
+                    ),
+            );
+    }
+}
+
+impl Render for PresetEditScreen {
+    fn render(
+        &mut self,
+        _window: &mut gpui::Window,
+        cx: &mut gpui::Context<Self>,
+    ) -> impl gpui::IntoElement {
+        let title = if self.preset_id.is_some() {
+            "Edit preset"
+        } else {
+            "New preset"
+        };
+
+        return div()
+            .size_full()
+            .flex()
+            .flex_col()
+            .child(
+                // title bar
+                TitleBar::new().child(div().child(title)).child(
+                    div().flex().items_center().gap_2().child(
+                        Button::new("close-preset-edit")
+                            .icon(Icon::new(Icon::empty()).path("icons/x.svg"))
+                            .ghost()
+                            .on_click(cx.listener(|_this, _event, _window, cx| {
+                                cx.emit(NavigationEvent {
+                                    screen: Screen::Settings,
+                                });
+                            })),
+                    ),
+                ),
+            )
+            .child(div().flex_grow().min_h_0().child(self.body(cx)))
+            .child(self.footer(cx));
+    }
+}
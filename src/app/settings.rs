@@ -1,32 +1,48 @@
+use gpui::prelude::FluentBuilder;
 use gpui::{
-    App, AppContext, Context, Div, Entity, EventEmitter, ParentElement, Render, SharedString,
-    Styled, Window, div,
+    App, AppContext, Context, Div, Entity, EventEmitter, InteractiveElement, ParentElement, Render,
+    SharedString, StatefulInteractiveElement, Styled, WeakEntity, Window, div, px,
 };
 use gpui_component::{
-    Icon, IconName, IndexPath, TitleBar,
+    Disableable, Icon, IconName, IndexPath, TitleBar,
     button::{Button, ButtonVariants},
+    checkbox::Checkbox,
+    input::{Input, InputEvent, InputState},
     label::Label,
-    list::{ListDelegate, ListItem, ListState},
+    list::{ListDelegate, ListEvent, ListItem, ListState},
 };
 
+use sqlx::types::chrono::Local;
+
+use crate::db::Database;
+use crate::duration::ClockFormat;
+use crate::engine::timer::SuspendPolicy;
 use crate::events::navigation::{NavigationEvent, Screen};
+use crate::events::preset::{DeletePresetEvent, EditPresetEvent, UsePresetEvent};
+use crate::session::TimerPreset;
+use crate::settings::AppSettings;
+use crate::statistics::DailyGoal;
 
 pub struct SettingScreen {
     preset_list: Entity<ListState<PresetListDelegate>>,
+    extend_increments: Entity<InputState>,
+    goal_target: Entity<InputState>,
+    deleting: Option<(i64, SharedString)>, // the preset whose delete is waiting on a confirmation
 }
 
 impl EventEmitter<NavigationEvent> for SettingScreen {}
+impl EventEmitter<EditPresetEvent> for SettingScreen {}
+impl EventEmitter<UsePresetEvent> for SettingScreen {}
+impl EventEmitter<DeletePresetEvent> for SettingScreen {}
 
 impl SettingScreen {
     pub fn new(cx: &mut Context<Self>, window: &mut Window) -> Self {
+        let screen = cx.entity().downgrade();
         let preset_list = cx.new(|cx| {
             ListState::new(
                 PresetListDelegate {
-                    items: vec![PresetItem {
-                        label: "Default".into(),
-                        id: "".into(),
-                        editable: false,
-                    }],
+                    screen,
+                    items: vec![],
                     selected_index: None,
                 },
                 window,
@@ -34,10 +50,144 @@ impl SettingScreen {
             )
         });
 
-        return SettingScreen { preset_list };
+        // open the preset editor when a preset is picked from the list
+        cx.subscribe(&preset_list, |_this, list, event: &ListEvent, cx| {
+            if let ListEvent::Confirm(ix) = event {
+                let preset_id = list.read(cx).delegate().items.get(ix.row).map(|i| i.id);
+                if let Some(preset_id) = preset_id {
+                    cx.emit(EditPresetEvent {
+                        preset_id: Some(preset_id),
+                    });
+                }
+            }
+        })
+        .detach();
+
+        cx.observe_global::<AppSettings>(|_this, cx| cx.notify())
+            .detach();
+
+        let increments = cx.global::<AppSettings>().increments_text();
+        let extend_increments = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("Minutes, e.g. 1, 5, 10")
+                .default_value(increments)
+        });
+
+        // only keep what parses, the field can hold half typed lists in the meantime
+        cx.subscribe(
+            &extend_increments,
+            |_this, input, event: &InputEvent, cx| {
+                if let InputEvent::Change = event {
+                    let value = input.read(cx).value();
+                    if let Some(increments) = AppSettings::parse_increments(&value) {
+                        AppSettings::update(cx, |settings| settings.extend_increments = increments);
+                    }
+                }
+            },
+        )
+        .detach();
+
+        let target = match cx.global::<AppSettings>().daily_goal {
+            DailyGoal::Off => String::new(),
+            DailyGoal::Minutes(target) | DailyGoal::Sessions(target) => target.to_string(),
+        };
+        let goal_target = cx.new(|cx| {
+            InputState::new(window, cx)
+                .placeholder("Target")
+                .default_value(target)
+        });
+
+        // keep the goal kind and only take targets that parse
+        cx.subscribe(&goal_target, |_this, input, event: &InputEvent, cx| {
+            if let InputEvent::Change = event {
+                let Some(target) = parse_target(&input.read(cx).value()) else {
+                    return;
+                };
+                let goal = match cx.global::<AppSettings>().daily_goal {
+                    DailyGoal::Off => return,
+                    DailyGoal::Minutes(_) => DailyGoal::Minutes(target),
+                    DailyGoal::Sessions(_) => DailyGoal::Sessions(target),
+                };
+                set_daily_goal(goal, cx);
+            }
+        })
+        .detach();
+
+        return SettingScreen {
+            preset_list,
+            extend_increments,
+            goal_target,
+            deleting: None,
+        };
     }
 
-    fn presets(&self) -> Div {
+    pub fn set_presets(&mut self, presets: &[TimerPreset], cx: &mut Context<Self>) {
+        let items = presets
+            .iter()
+            .filter_map(|preset| {
+                Some(PresetItem {
+                    id: preset.id?,
+                    label: preset.title.clone(),
+                })
+            })
+            .collect();
+
+        self.deleting = None;
+        self.preset_list.update(cx, |list, cx| {
+            let delegate = list.delegate_mut();
+            delegate.items = items;
+            delegate.selected_index = None;
+            cx.notify();
+        });
+    }
+
+    /// Ask before a preset is deleted, the list always keeps at least one
+    fn ask_delete(&mut self, preset_id: i64, title: SharedString, cx: &mut Context<Self>) {
+        self.deleting = Some((preset_id, title));
+        cx.notify();
+    }
+
+    fn confirm_delete(&mut self, cx: &mut Context<Self>) -> Div {
+        let Some((preset_id, title)) = self.deleting.clone() else {
+            return div();
+        };
+
+        return div()
+            .py_2()
+            .flex()
+            .flex_row()
+            .items_center()
+            .justify_between()
+            .gap_2()
+            .child(format!("Delete \"{title}\"? Its history is kept."))
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .gap_2()
+                    .child(
+                        Button::new("delete-preset-confirm")
+                            .label("Delete")
+                            .danger()
+                            .on_click(cx.listener(move |this, _event, _window, cx| {
+                                this.deleting = None;
+                                cx.emit(DeletePresetEvent { preset_id });
+                                cx.notify();
+                            })),
+                    )
+                    .child(
+                        Button::new("delete-preset-cancel")
+                            .label("Keep")
+                            .ghost()
+                            .on_click(cx.listener(|this, _event, _window, cx| {
+                                this.deleting = None;
+                                cx.notify();
+                            })),
+                    ),
+            );
+    }
+
+    fn presets(&mut self, cx: &mut Context<Self>) -> Div {
         return div()
             .child(
                 div()
@@ -46,13 +196,196 @@ impl SettingScreen {
                     .justify_between()
                     .items_center()
                     .child(Label::new("Presets"))
-                    .child(Button::new("new-preset-button").icon(IconName::Plus)),
+                    .child(
+                        Button::new("new-preset-button")
+                            .icon(IconName::Plus)
+                            .on_click(cx.listener(|_this, _event, _window, cx| {
+                                cx.emit(EditPresetEvent { preset_id: None });
+                            })),
+                    ),
+            )
+            .child(self.confirm_delete(cx))
+            // the list sizes itself to its parent, which scrolls
+            .child(div().h(px(200.)).child(self.preset_list.clone()));
+    }
+
+    fn suspend_policy(&self, cx: &mut Context<Self>) -> Div {
+        let current = cx.global::<AppSettings>().suspend_policy;
+        let options = [
+            (SuspendPolicy::Elapsed, "Keep counting"),
+            (SuspendPolicy::Paused, "Pause"),
+            (SuspendPolicy::Ask, "Ask me"),
+        ];
+
+        return div()
+            .flex()
+            .flex_col()
+            .gap_2()
+            .child(Label::new("When the computer sleeps"))
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .gap_2()
+                    .children(options.into_iter().map(|(policy, label)| {
+                        Button::new(policy.as_str())
+                            .label(label)
+                            .when_else(policy == current, |b| b.primary(), |b| b.ghost())
+                            .on_click(cx.listener(move |_this, _event, _window, cx| {
+                                AppSettings::update(cx, |settings| {
+                                    settings.suspend_policy = policy;
+                                });
+                                cx.notify();
+                            }))
+                    })),
+            );
+    }
+
+    fn clock_format(&self, cx: &mut Context<Self>) -> Div {
+        let current = cx.global::<AppSettings>().clock_format;
+        let options = [
+            (ClockFormat::Full, "01:05:00"),
+            (ClockFormat::HideZeroHours, "Hide hours at zero"),
+            (ClockFormat::Minutes, "65:00"),
+        ];
+
+        return div()
+            .flex()
+            .flex_col()
+            .gap_2()
+            .child(Label::new("Clock format"))
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .gap_2()
+                    .children(options.into_iter().map(|(format, label)| {
+                        Button::new(format.as_str())
+                            .label(label)
+                            .when_else(format == current, |b| b.primary(), |b| b.ghost())
+                            .on_click(cx.listener(move |_this, _event, _window, cx| {
+                                AppSettings::update(cx, |settings| {
+                                    settings.clock_format = format;
+                                });
+                                cx.notify();
+                            }))
+                    })),
             )
-            .child(self.preset_list.clone());
+            .child(
+                Checkbox::new("show-percent")
+                    .label("Show the percentage of the session left")
+                    .checked(cx.global::<AppSettings>().show_percent)
+                    .on_click(|checked: &bool, _window, cx| {
+                        let checked = *checked;
+                        AppSettings::update(cx, |settings| settings.show_percent = checked);
+                    }),
+            );
     }
 
-    fn body(&self) -> Div {
-        return div().p_2().flex().flex_col().gap_4().child(self.presets());
+    fn daily_goal(&self, cx: &mut Context<Self>) -> Div {
+        let current = cx.global::<AppSettings>().daily_goal;
+        let target = parse_target(&self.goal_target.read(cx).value());
+        let options = [
+            (DailyGoal::Off, "Off"),
+            (DailyGoal::Minutes(target.unwrap_or(120)), "Minutes"),
+            (DailyGoal::Sessions(target.unwrap_or(8)), "Sessions"),
+        ];
+
+        return div()
+            .flex()
+            .flex_col()
+            .gap_2()
+            .child(Label::new("Daily focus goal"))
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .gap_2()
+                    .children(options.into_iter().map(|(goal, label)| {
+                        let selected =
+                            std::mem::discriminant(&goal) == std::mem::discriminant(&current);
+                        Button::new(label)
+                            .label(label)
+                            .when_else(selected, |b| b.primary(), |b| b.ghost())
+                            .on_click(cx.listener(move |this, _event, window, cx| {
+                                // show the default target when the field had none
+                                if let DailyGoal::Minutes(target) | DailyGoal::Sessions(target) =
+                                    goal
+                                {
+                                    let target = target.to_string();
+                                    if this.goal_target.read(cx).value().as_ref() != target {
+                                        this.goal_target.update(cx, |input, cx| {
+                                            input.set_value(target, window, cx)
+                                        });
+                                    }
+                                }
+                                set_daily_goal(goal, cx);
+                                cx.notify();
+                            }))
+                    }))
+                    .when(current != DailyGoal::Off, |el| {
+                        el.child(div().w_24().child(Input::new(&self.goal_target)))
+                    }),
+            );
+    }
+
+    fn extend_increments(&self) -> Div {
+        return div()
+            .flex()
+            .flex_col()
+            .gap_2()
+            .child(Label::new("More time increments (minutes)"))
+            .child(Input::new(&self.extend_increments));
+    }
+
+    fn confirm_abandon(&self, cx: &mut Context<Self>) -> Checkbox {
+        return Checkbox::new("confirm-abandon")
+            .label("Ask before abandoning a focus session")
+            .checked(cx.global::<AppSettings>().confirm_abandon)
+            .on_click(|checked: &bool, _window, cx| {
+                let checked = *checked;
+                AppSettings::update(cx, |settings| settings.confirm_abandon = checked);
+            });
+    }
+
+    fn overtime(&self, cx: &mut Context<Self>) -> Checkbox {
+        return Checkbox::new("overtime")
+            .label("Keep counting past zero until I move on")
+            .checked(cx.global::<AppSettings>().overtime)
+            .on_click(|checked: &bool, _window, cx| {
+                let checked = *checked;
+                AppSettings::update(cx, |settings| settings.overtime = checked);
+            });
+    }
+
+    fn log_adhoc(&self, cx: &mut Context<Self>) -> Checkbox {
+        return Checkbox::new("log-adhoc")
+            .label("Keep quick timers and stopwatches in the history")
+            .checked(cx.global::<AppSettings>().log_adhoc)
+            .on_click(|checked: &bool, _window, cx| {
+                let checked = *checked;
+                AppSettings::update(cx, |settings| settings.log_adhoc = checked);
+            });
+    }
+
+    fn body(&mut self, cx: &mut Context<Self>) -> impl gpui::IntoElement {
+        return div()
+            .id("settings-body")
+            .size_full()
+            .p_2()
+            .flex()
+            .flex_col()
+            .gap_4()
+            .overflow_y_scroll()
+            .child(self.presets(cx))
+            .child(self.suspend_policy(cx))
+            .child(self.clock_format(cx))
+            .child(self.daily_goal(cx))
+            .child(self.extend_increments())
+            .child(self.confirm_abandon(cx))
+            .child(self.overtime(cx))
+            .child(self.log_adhoc(cx));
     }
 }
 
@@ -81,18 +414,38 @@ impl Render for SettingScreen {
                     ),
                 ),
             )
-            .child(self.body().flex_grow());
+            .child(div().flex_grow().min_h_0().child(self.body(cx)));
     }
 }
 
-#[allow(unused)]
+fn parse_target(value: &str) -> Option<u32> {
+    return value
+        .trim()
+        .parse::<u32>()
+        .ok()
+        .filter(|target| *target > 0);
+}
+
+/// Change the goal in the settings and keep it in the goal history from today on
+fn set_daily_goal(goal: DailyGoal, cx: &mut App) {
+    if cx.global::<AppSettings>().daily_goal == goal {
+        return;
+    }
+
+    AppSettings::update(cx, |settings| settings.daily_goal = goal);
+    let goals = cx.global::<Database>().goals();
+    let today = Local::now().date_naive();
+    cx.background_spawn(async move { goals.save(today, goal).await })
+        .detach_and_log_err(cx);
+}
+
 struct PresetItem {
-    id: SharedString,
+    id: i64,
     label: SharedString,
-    editable: bool,
 }
 
 struct PresetListDelegate {
+    screen: WeakEntity<SettingScreen>, // the row buttons report back through the screen
     items: Vec<PresetItem>,
     selected_index: Option<IndexPath>,
 }
@@ -110,10 +463,45 @@ impl ListDelegate for PresetListDelegate {
         _window: &mut Window,
         _cx: &mut App,
     ) -> Option<Self::Item> {
+        // the last preset can't go, there'd be nothing left to run
+        let only_preset = self.items.len() == 1;
         self.items.get(ix.row).map(|item| {
+            let preset_id = item.id;
+            let title = item.label.clone();
+            let screen = self.screen.clone();
             ListItem::new(ix)
                 .child(Label::new(item.label.clone()))
                 .selected(Some(ix) == self.selected_index)
+                .suffix(move |_window, _cx| {
+                    let use_screen = screen.clone();
+                    let delete_screen = screen.clone();
+                    let title = title.clone();
+                    div()
+                        .flex()
+                        .flex_row()
+                        .gap_1()
+                        .child(
+                            Button::new(("use-preset", preset_id as usize))
+                                .label("Use")
+                                .ghost()
+                                .on_click(move |_event, _window, cx| {
+                                    let _ = use_screen.update(cx, |_screen, cx| {
+                                        cx.emit(UsePresetEvent { preset_id })
+                                    });
+                                }),
+                        )
+                        .child(
+                            Button::new(("delete-preset", preset_id as usize))
+                                .icon(Icon::new(Icon::empty()).path("icons/x.svg"))
+                                .ghost()
+                                .disabled(only_preset)
+                                .on_click(move |_event, _window, cx| {
+                                    let _ = delete_screen.update(cx, |screen, cx| {
+                                        screen.ask_delete(preset_id, title.clone(), cx)
+                                    });
+                                }),
+                        )
+                })
         })
     }
 
@@ -0,0 +1,414 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Days, Local, NaiveDate, Utc};
+use gpui::prelude::FluentBuilder;
+use gpui::{
+    AppContext, Context, Div, Entity, EventEmitter, InteractiveElement, ParentElement, Render,
+    StatefulInteractiveElement, Styled, div, relative,
+};
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::label::Label;
+use gpui_component::{ActiveTheme as _, Icon, TitleBar};
+
+use crate::achievements::{Achievement, Progress};
+use crate::components::heatmap::Heatmap;
+use crate::db::Database;
+use crate::duration;
+use crate::events::navigation::{NavigationEvent, Screen};
+use crate::history::HistoryEntry;
+use crate::statistics::{self, DailyGoal, DayFocus, PeriodStats};
+
+/// Days shown in the bar chart
+const CHART_DAYS: u64 = 14;
+
+/// Focus and break totals worked out from the recorded history
+pub struct StatisticsScreen {
+    today: NaiveDate,
+    entries: Vec<HistoryEntry>, // everything from the earliest day any of the views need
+    is_loading: bool,
+    heatmap: Entity<Heatmap>,
+    goals: Vec<(NaiveDate, DailyGoal)>, // the goal history, oldest first
+    progress: Progress,
+    unlocked: Vec<(Achievement, DateTime<Utc>)>,
+}
+
+impl EventEmitter<NavigationEvent> for StatisticsScreen {}
+
+impl StatisticsScreen {
+    pub fn new(cx: &mut Context<Self>) -> Self {
+        let today = Local::now().date_naive();
+        return Self {
+            today,
+            entries: vec![],
+            is_loading: false,
+            heatmap: cx.new(|_| Heatmap::new(today)),
+            goals: vec![],
+            progress: Progress::default(),
+            unlocked: vec![],
+        };
+    }
+
+    /// Load the history again, called whenever the screen is opened
+    pub fn reload(&mut self, cx: &mut Context<Self>) {
+        let today = Local::now().date_naive();
+        let chart_start = today - Days::new(CHART_DAYS - 1);
+        let from = statistics::start_of_month(today)
+            .min(statistics::start_of_week(today))
+            .min(chart_start);
+        let to = statistics::start_of_day(today + Days::new(1));
+
+        self.is_loading = true;
+        let history = cx.global::<Database>().history();
+        let goal_repository = cx.global::<Database>().goals();
+        let achievement_repository = cx.global::<Database>().achievements();
+        cx.spawn(async move |this, cx| -> anyhow::Result<()> {
+            let entries = history
+                .list_between(statistics::start_of_day(from), to)
+                .await?;
+            // streaks and achievements go back to the start, the heatmap keeps the last year
+            let days = history
+                .focus_per_day(DateTime::<Utc>::UNIX_EPOCH, to)
+                .await?;
+            let goals = goal_repository.list().await?;
+            let unlocked = achievement_repository.list().await?;
+            let progress = Progress::from_days(&days, &goals, today);
+            let first_day = Heatmap::first_day(today);
+            let year = days
+                .into_iter()
+                .filter(|day| day.day >= first_day)
+                .collect();
+            this.update(cx, |screen, cx| {
+                screen.today = today;
+                screen.entries = entries;
+                screen.goals = goals;
+                screen.progress = progress;
+                screen.unlocked = unlocked;
+                screen.is_loading = false;
+                screen
+                    .heatmap
+                    .update(cx, |heatmap, cx| heatmap.set_days(today, year, cx));
+                cx.notify();
+            })?;
+            return Ok(());
+        })
+        .detach_and_log_err(cx);
+        cx.notify();
+    }
+
+    fn stats_since(&self, day: NaiveDate) -> PeriodStats {
+        return PeriodStats::from_entries(
+            self.entries
+                .iter()
+                .filter(|entry| statistics::local_day(entry.started_at) >= day),
+        );
+    }
+
+    fn row(&self, label: &str, value: String, cx: &mut Context<Self>) -> Div {
+        return div()
+            .flex()
+            .flex_row()
+            .justify_between()
+            .gap_2()
+            .text_sm()
+            .child(
+                div()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(label.to_string()),
+            )
+            .child(value);
+    }
+
+    fn period(&self, title: &'static str, stats: PeriodStats, cx: &mut Context<Self>) -> Div {
+        return div()
+            .flex_1()
+            .p_2()
+            .flex()
+            .flex_col()
+            .gap_1()
+            .border_1()
+            .border_color(cx.theme().border)
+            .rounded_lg()
+            .child(Label::new(title))
+            .child(self.row("Focus", duration::format_minutes(stats.focus_time), cx))
+            .child(self.row("Break", duration::format_minutes(stats.break_time), cx))
+            .child(self.row("Completed", stats.completed.to_string(), cx))
+            .child(self.row("Abandoned", stats.abandoned.to_string(), cx))
+            .child(self.row(
+                "Avg. focus",
+                duration::format_minutes(stats.average_focus()),
+                cx,
+            ))
+            .child(self.row(
+                "Interruptions (int. / ext.)",
+                format!(
+                    "{} / {}",
+                    stats.internal_interruptions, stats.external_interruptions
+                ),
+                cx,
+            ));
+    }
+
+    /// Focus time of the last `CHART_DAYS` days, each bar scaled against the busiest one.
+    /// Days that met the goal of the time are drawn in the success color
+    fn chart(&self, cx: &mut Context<Self>) -> Div {
+        let days = statistics::daily_focus(&self.entries, self.today, CHART_DAYS);
+        let busiest = days
+            .iter()
+            .map(|day| day.focus)
+            .max()
+            .unwrap_or(Duration::ZERO);
+
+        let goals: Vec<DailyGoal> = days
+            .iter()
+            .map(|day| statistics::goal_on(day.day, &self.goals))
+            .collect();
+        let days_with_goal = goals.iter().filter(|goal| **goal != DailyGoal::Off).count();
+        let days_met = days
+            .iter()
+            .zip(&goals)
+            .filter(|(day, goal)| goal.is_met(day.focus, day.completed))
+            .count();
+
+        return div()
+            .flex()
+            .flex_col()
+            .gap_2()
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .justify_between()
+                    .child(Label::new("Focus per day"))
+                    .when(days_with_goal > 0, |el| {
+                        el.child(
+                            div()
+                                .text_sm()
+                                .text_color(cx.theme().muted_foreground)
+                                .child(format!("Goal met {days_met} of {days_with_goal} days")),
+                        )
+                    }),
+            )
+            .child(div().h_32().flex().flex_row().items_end().gap_1().children(
+                days.into_iter().zip(goals).map(|(day, goal)| {
+                    let met = goal.is_met(day.focus, day.completed);
+                    let DayFocus { day, focus, .. } = day;
+                    let height = if busiest.is_zero() {
+                        0.
+                    } else {
+                        focus.as_secs_f32() / busiest.as_secs_f32()
+                    };
+
+                    div()
+                        .flex_1()
+                        .h_full()
+                        .flex()
+                        .flex_col()
+                        .justify_end()
+                        .items_center()
+                        .gap_1()
+                        .child(
+                            div()
+                                .w_full()
+                                .h(relative(height.max(0.02)))
+                                .rounded_sm()
+                                .bg(if met {
+                                    cx.theme().success
+                                } else if focus.is_zero() {
+                                    cx.theme().border
+                                } else {
+                                    cx.theme().primary
+                                }),
+                        )
+                        .child(
+                            div()
+                                .text_xs()
+                                .text_color(cx.theme().muted_foreground)
+                                .child(day.format("%d").to_string()),
+                        )
+                }),
+            ));
+    }
+
+    fn streaks(&self, cx: &mut Context<Self>) -> Div {
+        let days = |count: u32| {
+            return if count == 1 {
+                "1 day".to_string()
+            } else {
+                format!("{count} days")
+            };
+        };
+
+        return div()
+            .flex()
+            .flex_row()
+            .gap_2()
+            .child(
+                div()
+                    .flex_1()
+                    .p_2()
+                    .border_1()
+                    .border_color(cx.theme().border)
+                    .rounded_lg()
+                    .child(self.row("Current streak", days(self.progress.current_streak), cx)),
+            )
+            .child(
+                div()
+                    .flex_1()
+                    .p_2()
+                    .border_1()
+                    .border_color(cx.theme().border)
+                    .rounded_lg()
+                    .child(self.row("Longest streak", days(self.progress.longest_streak), cx)),
+            );
+    }
+
+    /// Every achievement, with when it was unlocked or how far along it is
+    fn achievements(&self, cx: &mut Context<Self>) -> Div {
+        return div()
+            .flex()
+            .flex_col()
+            .gap_2()
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .justify_between()
+                    .child(Label::new("Achievements"))
+                    .child(
+                        div()
+                            .text_sm()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(format!(
+                                "{} of {} unlocked",
+                                self.unlocked.len(),
+                                Achievement::ALL.len()
+                            )),
+                    ),
+            )
+            .children(Achievement::ALL.into_iter().map(|achievement| {
+                let unlocked_at = self
+                    .unlocked
+                    .iter()
+                    .find(|(unlocked, _)| *unlocked == achievement)
+                    .map(|(_, at)| *at);
+                let status = match unlocked_at {
+                    Some(at) => {
+                        format!("Unlocked {}", at.with_timezone(&Local).format("%-d %b %Y"))
+                    }
+                    None => {
+                        let (done, target) = achievement.progress(&self.progress);
+                        format!("{}/{}", done.min(target), target)
+                    }
+                };
+
+                div()
+                    .p_2()
+                    .flex()
+                    .flex_row()
+                    .justify_between()
+                    .items_center()
+                    .gap_2()
+                    .border_1()
+                    .rounded_lg()
+                    .when_else(
+                        unlocked_at.is_some(),
+                        |el| el.border_color(cx.theme().success),
+                        |el| el.border_color(cx.theme().border).opacity(0.6),
+                    )
+                    .child(
+                        div()
+                            .flex()
+                            .flex_col()
+                            .child(div().text_sm().child(achievement.title()))
+                            .child(
+                                div()
+                                    .text_xs()
+                                    .text_color(cx.theme().muted_foreground)
+                                    .child(achievement.description()),
+                            ),
+                    )
+                    .child(
+                        div()
+                            .text_xs()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(status),
+                    )
+            }));
+    }
+}
+
+impl Render for StatisticsScreen {
+    fn render(
+        &mut self,
+        _window: &mut gpui::Window,
+        cx: &mut gpui::Context<Self>,
+    ) -> impl gpui::IntoElement {
+        let today = self.stats_since(self.today);
+        let week = self.stats_since(statistics::start_of_week(self.today));
+        let month = self.stats_since(statistics::start_of_month(self.today));
+
+        return div()
+            .size_full()
+            .flex()
+            .flex_col()
+            .child(
+                TitleBar::new().child(div().child("Statistics")).child(
+                    div()
+                        .flex()
+                        .items_center()
+                        .gap_2()
+                        .child(
+                            Button::new("statistics-history")
+                                .label("History")
+                                .ghost()
+                                .on_click(cx.listener(|_this, _event, _window, cx| {
+                                    cx.emit(NavigationEvent {
+                                        screen: Screen::History,
+                                    });
+                                })),
+                        )
+                        .child(
+                            Button::new("statistics-close")
+                                .icon(Icon::new(Icon::empty()).path("icons/x.svg"))
+                                .ghost()
+                                .on_click(cx.listener(|_this, _event, _window, cx| {
+                                    cx.emit(NavigationEvent {
+                                        screen: Screen::Timer,
+                                    });
+                                })),
+                        ),
+                ),
+            )
+            .child(
+                div()
+                    .id("statistics-body")
+                    .overflow_y_scroll()
+                    .p_4()
+                    .flex()
+                    .flex_col()
+                    .flex_grow()
+                    .gap_4()
+                    .when(self.is_loading, |el| {
+                        el.child(
+                            div()
+                                .text_sm()
+                                .text_color(cx.theme().muted_foreground)
+                                .child("Loading…"),
+                        )
+                    })
+                    .child(
+                        div()
+                            .flex()
+                            .flex_row()
+                            .gap_2()
+                            .child(self.period("Today", today, cx))
+                            .child(self.period("This week", week, cx))
+                            .child(self.period("This month", month, cx)),
+                    )
+                    .child(self.streaks(cx))
+                    .child(self.chart(cx))
+                    .child(self.heatmap.clone())
+                    .child(self.achievements(cx)),
+            );
+    }
+}
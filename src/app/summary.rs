@@ -0,0 +1,179 @@
+use gpui::{Context, Div, EventEmitter, ParentElement, Render, Styled, div};
+use gpui_component::button::{Button, ButtonVariants};
+use gpui_component::label::Label;
+use gpui_component::{ActiveTheme as _, TitleBar};
+
+use crate::duration;
+use crate::engine::summary::RunSummary;
+use crate::events::summary::SummaryActionEvent;
+use crate::session::TimerPreset;
+
+/// Shown once the last session of a preset is done
+pub struct SummaryScreen {
+    preset: TimerPreset,
+    summary: RunSummary,
+    presets: Vec<TimerPreset>,
+}
+
+impl EventEmitter<SummaryActionEvent> for SummaryScreen {}
+
+impl SummaryScreen {
+    pub fn new() -> Self {
+        return SummaryScreen {
+            preset: TimerPreset::default(),
+            summary: RunSummary::default(),
+            presets: vec![],
+        };
+    }
+
+    pub fn show(&mut self, preset: TimerPreset, summary: RunSummary, cx: &mut Context<Self>) {
+        self.preset = preset;
+        self.summary = summary;
+        cx.notify();
+    }
+
+    pub fn set_presets(&mut self, presets: &[TimerPreset], cx: &mut Context<Self>) {
+        self.presets = presets.to_vec();
+        cx.notify();
+    }
+
+    fn stat(&self, label: &str, value: String, cx: &mut Context<Self>) -> Div {
+        return div()
+            .flex()
+            .flex_row()
+            .justify_between()
+            .py_1()
+            .border_b_1()
+            .border_color(cx.theme().border)
+            .child(
+                div()
+                    .text_color(cx.theme().muted_foreground)
+                    .child(label.to_string()),
+            )
+            .child(value);
+    }
+
+    fn stats(&self, cx: &mut Context<Self>) -> Div {
+        let summary = &self.summary;
+        return div()
+            .flex()
+            .flex_col()
+            .child(self.stat(
+                "Sessions completed",
+                format!("{} of {}", summary.completed, summary.sessions_total),
+                cx,
+            ))
+            .child(self.stat(
+                "Focus time",
+                duration::format_minutes(summary.focus_time),
+                cx,
+            ))
+            .child(self.stat(
+                "Break time",
+                duration::format_minutes(summary.break_time),
+                cx,
+            ))
+            .child(self.stat(
+                "Extended by",
+                duration::format_minutes(summary.extended),
+                cx,
+            ))
+            .child(self.stat("Overtime", duration::format_minutes(summary.overtime), cx))
+            .child(self.stat("Skipped", summary.skipped.to_string(), cx))
+            .child(self.stat("Pauses", summary.pauses.to_string(), cx))
+            .child(self.stat("Time paused", duration::format_minutes(summary.paused), cx))
+            .child(self.stat("Abandoned", summary.abandoned.to_string(), cx))
+            .child(self.stat(
+                "Interruptions",
+                format!(
+                    "{} internal, {} external",
+                    summary.internal_interruptions, summary.external_interruptions
+                ),
+                cx,
+            ));
+    }
+
+    /// The other presets, any of them can be started straight from here
+    fn other_presets(&self, cx: &mut Context<Self>) -> Option<Div> {
+        let others: Vec<&TimerPreset> = self
+            .presets
+            .iter()
+            .filter(|preset| preset.id.is_some() && preset.id != self.preset.id)
+            .collect();
+        if others.is_empty() {
+            return None;
+        }
+
+        return Some(
+            div()
+                .flex()
+                .flex_col()
+                .gap_2()
+                .child(Label::new("Start another preset"))
+                .child(div().flex().flex_row().flex_wrap().gap_2().children(
+                    others.into_iter().filter_map(|preset| {
+                        let id = preset.id?;
+                        Some(
+                            Button::new(("summary-preset", id as usize))
+                                .label(preset.title.clone())
+                                .ghost()
+                                .on_click(cx.listener(move |_this, _event, _window, cx| {
+                                    cx.emit(SummaryActionEvent::StartPreset(id));
+                                })),
+                        )
+                    }),
+                )),
+        );
+    }
+
+    fn footer(&self, cx: &mut Context<Self>) -> Div {
+        return div()
+            .p_4()
+            .flex()
+            .flex_row()
+            .justify_center()
+            .gap_2()
+            .child(
+                Button::new("summary-repeat")
+                    .label("Repeat preset")
+                    .primary()
+                    .on_click(cx.listener(|_this, _event, _window, cx| {
+                        cx.emit(SummaryActionEvent::RepeatPreset);
+                    })),
+            )
+            .child(
+                Button::new("summary-finish")
+                    .label("Finish for today")
+                    .ghost()
+                    .on_click(cx.listener(|_this, _event, _window, cx| {
+                        cx.emit(SummaryActionEvent::FinishForToday);
+                    })),
+            );
+    }
+}
+
+impl Render for SummaryScreen {
+    fn render(
+        &mut self,
+        _window: &mut gpui::Window,
+        cx: &mut gpui::Context<Self>,
+    ) -> impl gpui::IntoElement {
+        let other_presets = self.other_presets(cx);
+        return div()
+            .size_full()
+            .flex()
+            .flex_col()
+            .child(TitleBar::new().child(div().child(format!("{} ・ done", self.preset.title))))
+            .child(
+                div()
+                    .p_4()
+                    .flex()
+                    .flex_col()
+                    .flex_grow()
+                    .gap_4()
+                    .child(self.stats(cx))
+                    .children(other_presets),
+            )
+            .child(self.footer(cx));
+    }
+}
@@ -1,29 +1,64 @@
+use std::mem;
+use std::time::{Duration, SystemTime};
+
+use chrono::{DateTime, Days, Local, Utc};
 use gpui::prelude::FluentBuilder;
 use gpui::{
-    AppContext, Context, Div, Entity, EventEmitter, InteractiveElement, ParentElement, Render,
-    Styled, div, px, rgb, svg, white,
+    App, AppContext, Context, Div, Entity, EventEmitter, InteractiveElement, ParentElement, Render,
+    SharedString, Styled, Task, Window, div, px, relative, rgb, svg, white,
 };
 use gpui_component::TitleBar;
 use gpui_component::button::{Button, ButtonVariants};
-use gpui_component::{ActiveTheme as _, IconName};
+use gpui_component::input::{Input, InputEvent, InputState};
+use gpui_component::notification::Notification;
+use gpui_component::{ActiveTheme as _, Icon, IconName, Sizable, WindowExt};
 
-use crate::components::timeline::TimeLine;
-use crate::components::timer::{Timer, TimerCompletedEvent, TimerTickEvent};
+use crate::components::timeline::{TimeLine, TimeLineSegmentClickedEvent};
+use crate::components::timer::{
+    PresetCompletedEvent, SessionEndedEvent, SessionStartedEvent, Timer, TimerStateChangedEvent,
+    TimerTickEvent,
+};
+use crate::constants::{ADHOC_LABEL, ONE_MIN_MS};
+use crate::db::Database;
+use crate::duration;
+use crate::engine::timer::{EngineState, SessionRun};
+use crate::events::history::HistoryChangedEvent;
 use crate::events::navigation::{NavigationEvent, Screen};
-use crate::session::{Session, SessionKind, TimerPreset};
+use crate::events::summary::PresetFinishedEvent;
+use crate::history::{HistoryEntry, InterruptionKind, SessionOutcome};
+use crate::repository::run_state::SavedRun;
+use crate::session::{SessionKind, TimerPreset};
+use crate::settings::AppSettings;
+use crate::statistics::{self, DailyGoal, DayFocus};
 
 pub struct TimerScreen {
     timer: Entity<Timer>,
     timeline: Entity<TimeLine>,
-    session_index: usize,
-    preset: TimerPreset,
+    session_started_at: Option<DateTime<Utc>>,
+    pending_resume: Option<(TimerPreset, SavedRun)>, // a run left over from the last launch
+    confirming_abandon: bool, // stop was pressed during a focus session, waiting for an answer
+    quick_duration: Entity<InputState>,
+    quick_error: Option<SharedString>,
+    quick_previous: Option<TimerPreset>, // the preset to go back to once a quick run is over
+    interruption_note: Entity<InputState>,
+    today_focus: DayFocus, // focus recorded so far on the day the goal is tracked for
+    run_state_task: Task<()>, // the last write to the saved run, each one waits for the one before
 }
 
 impl EventEmitter<NavigationEvent> for TimerScreen {}
+impl EventEmitter<PresetFinishedEvent> for TimerScreen {}
+impl EventEmitter<HistoryChangedEvent> for TimerScreen {}
 
 impl TimerScreen {
-    pub fn new(cx: &mut Context<Self>) -> Self {
-        let timer = cx.new(|_| Timer::new());
+    pub fn new(cx: &mut Context<Self>, window: &mut Window) -> Self {
+        let settings = cx.global::<AppSettings>().clone();
+        let timer = cx.new(|_| {
+            let mut timer = Timer::new(TimerPreset::default());
+            timer.set_suspend_policy(settings.suspend_policy);
+            timer.set_overtime(settings.overtime);
+            timer.set_display(settings.clock_format, settings.show_percent);
+            timer
+        });
         let timeline = cx.new(|_| TimeLine::new());
 
         // update the timeline on every tick
@@ -34,54 +69,488 @@ impl TimerScreen {
                 timeline.update(cx, |timeline, _cx| {
                     timeline.current_progress = percentage_completed;
                 });
+                // a minutes goal counts the running session as it goes
+                if let DailyGoal::Minutes(_) = cx.global::<AppSettings>().daily_goal {
+                    cx.notify();
+                }
             }
         })
         .detach();
 
-        // subscription to check for timer completed event
+        // follow the engine onto the next session
         cx.subscribe(&timer, {
             let timeline = timeline.clone();
-            move |parent, timer, _event: &TimerCompletedEvent, cx| {
-                if parent.session_index == parent.preset.sessions.len() - 1 {
-                    // completed
-                    return;
-                }
+            move |parent, timer, event: &SessionStartedEvent, cx| {
+                let index = event.index;
+                let cycle = timer.read(cx).engine().cycle();
+                parent.session_started_at = Some(Utc::now());
+                timeline.update(cx, |e, _cx| {
+                    e.active_index = index;
+                    e.cycle = cycle;
+                });
+            }
+        })
+        .detach();
 
-                let new_sess_index = parent.session_index + 1;
-                parent.session_index = new_sess_index;
-                timeline.update(cx, |e, _cx| e.active_index = new_sess_index);
-                let session = parent.session();
+        // every session that stops counting, however it ended, goes to the history
+        cx.subscribe(&timer, |parent, _timer, event: &SessionEndedEvent, cx| {
+            parent.record_session(&event.run, cx);
+        })
+        .detach();
 
-                // start the next session
-                timer.update(cx, |e, cx| {
-                    e.start(session, cx);
-                });
+        // hand the run over to the summary once the last session is done
+        cx.subscribe(&timer, |parent, timer, event: &PresetCompletedEvent, cx| {
+            // quick runs just go back to the preset, there's nothing to sum up
+            if parent.quick_previous.is_some() {
+                return;
             }
+
+            let preset = timer.read(cx).engine().preset().clone();
+            cx.emit(PresetFinishedEvent {
+                preset,
+                summary: event.summary.clone(),
+            });
+        })
+        .detach();
+
+        // jump straight to a session picked on the timeline
+        cx.subscribe(
+            &timeline,
+            |parent, _timeline, event: &TimeLineSegmentClickedEvent, cx| {
+                let index = event.index;
+                parent
+                    .timer
+                    .update(cx, |timer, cx| timer.jump_to(index, cx));
+            },
+        )
+        .detach();
+
+        // keep the saved run in step so it can be resumed after a restart,
+        // and hand the timer back to the preset once a quick run is over
+        cx.subscribe(
+            &timer,
+            |parent, timer, _event: &TimerStateChangedEvent, cx| {
+                parent.persist_run_state(cx);
+                if parent.quick_previous.is_some() && timer.read(cx).is_idle() {
+                    parent.end_quick_run(cx);
+                }
+            },
+        )
+        .detach();
+
+        let interruption_note = cx
+            .new(|cx| InputState::new(window, cx).placeholder("What interrupted you? (optional)"));
+
+        let quick_duration =
+            cx.new(|cx| InputState::new(window, cx).placeholder("Quick timer, e.g. 7m30s"));
+        cx.subscribe(
+            &quick_duration,
+            |parent, _input, event: &InputEvent, cx| match event {
+                InputEvent::PressEnter { .. } => parent.start_quick_timer(cx),
+                InputEvent::Change => parent.quick_error = None,
+                _ => {}
+            },
+        )
+        .detach();
+
+        cx.observe_global::<AppSettings>(|parent, cx| {
+            let settings = cx.global::<AppSettings>().clone();
+            parent.timer.update(cx, |timer, cx| {
+                timer.set_suspend_policy(settings.suspend_policy);
+                timer.set_overtime(settings.overtime);
+                timer.set_display(settings.clock_format, settings.show_percent);
+                cx.notify();
+            });
+            cx.notify();
         })
         .detach();
 
-        return TimerScreen {
+        let mut screen = TimerScreen {
             timer,
             timeline,
-            preset: TimerPreset::default(),
-            session_index: 0,
+            session_started_at: None,
+            pending_resume: None,
+            confirming_abandon: false,
+            quick_duration,
+            quick_error: None,
+            quick_previous: None,
+            interruption_note,
+            today_focus: DayFocus::empty(Local::now().date_naive()),
+            run_state_task: Task::ready(()),
         };
+        screen.reload_today_focus(cx);
+        return screen;
     }
 
-    fn session(&self) -> &Session {
-        let i = self.session_index;
-        return self.preset.sessions.get(i).unwrap();
+    /// Load today's focus from the history again, for the daily goal in the title bar
+    pub fn reload_today_focus(&mut self, cx: &mut Context<Self>) {
+        let today = Local::now().date_naive();
+        let history = cx.global::<Database>().history();
+        cx.spawn(async move |this, cx| -> anyhow::Result<()> {
+            let days = history
+                .focus_per_day(
+                    statistics::start_of_day(today),
+                    statistics::start_of_day(today + Days::new(1)),
+                )
+                .await?;
+            this.update(cx, |screen, cx| {
+                screen.today_focus = days
+                    .into_iter()
+                    .next()
+                    .unwrap_or_else(|| DayFocus::empty(today));
+                cx.notify();
+            })?;
+            return Ok(());
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Today's focus including the focus session under way, quick runs don't count
+    fn focus_so_far(&self, cx: &App) -> DayFocus {
+        let today = Local::now().date_naive();
+        let mut focus = if self.today_focus.day == today {
+            self.today_focus
+        } else {
+            DayFocus::empty(today)
+        };
+
+        let engine = self.timer.read(cx).engine();
+        if self.quick_previous.is_none()
+            && engine.in_session()
+            && engine
+                .session()
+                .is_some_and(|s| s.kind == SessionKind::WORK)
+        {
+            focus.focus += engine.elapsed();
+        }
+
+        return focus;
     }
 
-    fn timer_area(&mut self) -> Div {
-        let current_session = self.session();
+    /// "3/8 today" with a thin bar under it, in the success color once the goal is met
+    fn goal_progress(&self, cx: &mut Context<Self>) -> Option<Div> {
+        let goal = cx.global::<AppSettings>().daily_goal;
+        let today = self.focus_so_far(cx);
+        let (done, target) = goal.progress(today.focus, today.completed)?;
+        let label = match goal {
+            DailyGoal::Minutes(_) => format!("{done}/{target} min today"),
+            _ => format!("{done}/{target} today"),
+        };
+        let color = if done >= target {
+            cx.theme().success
+        } else {
+            cx.theme().primary
+        };
+
+        return Some(
+            div()
+                .flex()
+                .flex_col()
+                .items_end()
+                .gap(px(2.))
+                .text_xs()
+                .child(label)
+                .child(
+                    div()
+                        .w_16()
+                        .h(px(3.))
+                        .rounded_sm()
+                        .bg(cx.theme().border)
+                        .child(
+                            div()
+                                .h_full()
+                                .w(relative((done as f32 / target as f32).min(1.)))
+                                .rounded_sm()
+                                .bg(color),
+                        ),
+                ),
+        );
+    }
+
+    pub fn preset_id(&self, cx: &App) -> Option<i64> {
+        return self.timer.read(cx).engine().preset().id;
+    }
+
+    /// Swap the active preset, ignored while a session is in progress
+    pub fn set_preset(&mut self, preset: TimerPreset, cx: &mut Context<Self>) {
+        let changed = self
+            .timer
+            .update(cx, |timer, cx| timer.set_preset(preset, cx));
+        if changed {
+            self.reset_timeline(cx);
+        }
+    }
+
+    /// Pick up a reloaded copy of the active preset, a stopped run keeps its place
+    /// unless the sessions were edited
+    pub fn refresh_preset(&mut self, preset: TimerPreset, cx: &mut Context<Self>) {
+        let changed = self
+            .timer
+            .update(cx, |timer, cx| timer.refresh_preset(preset, cx));
+        if changed {
+            self.reset_timeline(cx);
+        }
+    }
+
+    fn reset_timeline(&mut self, cx: &mut Context<Self>) {
+        let preset = self.timer.read(cx).engine().preset().clone();
+        self.timeline.update(cx, move |entity, _cx| {
+            entity.active_index = 0;
+            entity.cycle = 0;
+            entity.current_progress = 0.;
+            entity.update_segments(&preset);
+        });
+        cx.notify();
+    }
+
+    /// Offer to pick up a run that was in progress when the app closed
+    pub fn offer_resume(&mut self, preset: TimerPreset, saved: SavedRun, cx: &mut Context<Self>) {
+        if !self.timer.read(cx).is_idle() {
+            return;
+        }
+
+        self.pending_resume = Some((preset, saved));
+        cx.notify();
+    }
+
+    /// Load the offered run into the timer, returns false if it no longer fits its preset
+    fn restore_saved_run(&mut self, cx: &mut Context<Self>) -> bool {
+        let Some((preset, saved)) = self.pending_resume.take() else {
+            return false;
+        };
+
+        let timeline_preset = preset.clone();
+        let restored = self
+            .timer
+            .update(cx, |timer, cx| timer.restore(preset, &saved.snapshot, cx));
+        if !restored {
+            cx.notify();
+            return false;
+        }
+
+        self.session_started_at = Some(saved.session_started_at);
+        let index = saved.snapshot.session_index;
+        let cycle = saved.snapshot.cycle;
+        let progress = self.timer.read(cx).engine().percent_completed();
+        self.timeline.update(cx, move |entity, _cx| {
+            entity.update_segments(&timeline_preset);
+            entity.active_index = index;
+            entity.cycle = cycle;
+            entity.current_progress = progress;
+        });
+
+        cx.notify();
+        return restored;
+    }
+
+    fn resume_run(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        if self.restore_saved_run(cx) {
+            return;
+        }
+
+        // the preset changed since the run was saved, nothing sensible to resume
+        self.persist_run_state(cx);
+        window.push_notification(
+            Notification::new()
+                .title("Couldn't resume the run")
+                .message("The preset changed since the run was saved, so it was dropped."),
+            cx,
+        );
+    }
+
+    /// Run the interrupted session again, what was done of it goes in the history as stopped
+    fn restart_run(&mut self, cx: &mut Context<Self>) {
+        let Some((preset, saved)) = self.pending_resume.take() else {
+            return;
+        };
+
+        self.record_interrupted(&preset, &saved, cx);
+
+        let timeline_preset = preset.clone();
+        self.timeline.update(cx, move |entity, _cx| {
+            entity.update_segments(&timeline_preset);
+        });
+        self.timer
+            .update(cx, |timer, cx| timer.restart(preset, &saved.snapshot, cx));
+        self.sync_timeline(cx);
+        cx.notify();
+    }
+
+    /// Drop the interrupted run, what was done of its session goes in the history as stopped
+    fn discard_run(&mut self, cx: &mut Context<Self>) {
+        let Some((preset, saved)) = self.pending_resume.take() else {
+            return;
+        };
+
+        self.record_interrupted(&preset, &saved, cx);
+        self.persist_run_state(cx);
+        cx.notify();
+    }
+
+    /// Log the session a saved run was on before the app closed, as far as the save knows
+    fn record_interrupted(
+        &mut self,
+        preset: &TimerPreset,
+        saved: &SavedRun,
+        cx: &mut Context<Self>,
+    ) {
+        let Some(run) = saved.snapshot.interrupted_run(preset) else {
+            return;
+        };
+
+        let started_at = saved.session_started_at;
+        let ended_at = started_at
+            + chrono::Duration::from_std(run.elapsed + run.overtime + run.paused())
+                .unwrap_or_default();
+        let entry = HistoryEntry::from_run(&run, preset.id, started_at, ended_at);
+        self.record_entry(entry, cx);
+    }
+
+    /// Point the timeline at wherever the engine is now
+    fn sync_timeline(&mut self, cx: &mut Context<Self>) {
+        let engine = self.timer.read(cx).engine();
+        let index = engine.session_index();
+        let cycle = engine.cycle();
+        let progress = engine.percent_completed();
+        self.timeline.update(cx, move |entity, _cx| {
+            entity.active_index = index;
+            entity.cycle = cycle;
+            entity.current_progress = progress;
+        });
+    }
+
+    /// Stop asks first when it would throw away a focus session in progress
+    fn request_stop(&mut self, cx: &mut Context<Self>) {
+        let timer = self.timer.read(cx);
+        let in_focus = timer.in_session()
+            && timer
+                .engine()
+                .session()
+                .is_some_and(|session| session.kind == SessionKind::WORK);
+
+        if in_focus && cx.global::<AppSettings>().confirm_abandon {
+            self.confirming_abandon = true;
+            cx.notify();
+            return;
+        }
+
+        self.stop_session(cx);
+    }
+
+    /// End the current session, Start runs it again
+    fn stop_session(&mut self, cx: &mut Context<Self>) {
+        self.confirming_abandon = false;
+        self.timer.update(cx, |timer, cx| timer.stop(cx));
+    }
+
+    /// End the current session and go back to the first one
+    pub fn reset_preset(&mut self, cx: &mut Context<Self>) {
+        self.confirming_abandon = false;
+        self.timer.update(cx, |timer, cx| timer.reset(cx));
+    }
+
+    fn persist_run_state(&mut self, cx: &mut Context<Self>) {
+        let engine = self.timer.read(cx).engine();
+        let saved = match (
+            engine.snapshot(),
+            engine.preset().id,
+            self.session_started_at,
+        ) {
+            (Some(snapshot), Some(preset_id), Some(session_started_at)) => Some(SavedRun {
+                preset_id,
+                snapshot,
+                session_started_at,
+            }),
+            _ => None,
+        };
+
+        let repository = cx.global::<Database>().run_state();
+        let previous = mem::replace(&mut self.run_state_task, Task::ready(()));
+        self.run_state_task = cx.background_spawn(async move {
+            previous.await;
+            let result = match saved {
+                Some(run) => repository.save(&run).await,
+                None => repository.clear().await,
+            };
+            if let Err(err) = result {
+                log::error!("failed to save the run state: {err}");
+            }
+        });
+    }
+
+    /// Write a finished session run to the history table
+    fn record_session(&mut self, run: &SessionRun, cx: &mut Context<Self>) {
+        // a session skipped while it was lined up never started, it ends right away
+        let started_at = match self.session_started_at.take() {
+            Some(at) => at,
+            None if run.elapsed.is_zero() => Utc::now(),
+            None => return,
+        };
+
+        // quick runs don't belong to a preset, they're only kept if asked for
+        let session_title = if self.quick_previous.is_some() {
+            if !cx.global::<AppSettings>().log_adhoc {
+                return;
+            }
+            SharedString::from(ADHOC_LABEL)
+        } else {
+            run.session.title.clone()
+        };
+
+        let preset_id = self.timer.read(cx).engine().preset().id;
+        let entry = HistoryEntry {
+            session_title,
+            adhoc: self.quick_previous.is_some(),
+            ..HistoryEntry::from_run(run, preset_id, started_at, Utc::now())
+        };
+
+        self.record_entry(entry, cx);
+    }
+
+    /// Count the entry towards today's goal and write it to the history table
+    fn record_entry(&mut self, entry: HistoryEntry, cx: &mut Context<Self>) {
+        // a session left over from an earlier day doesn't count towards today
+        let day = statistics::local_day(entry.started_at);
+        if entry.is_focus() && day == Local::now().date_naive() {
+            if self.today_focus.day != day {
+                self.today_focus = DayFocus::empty(day);
+            }
+            self.today_focus.focus += entry.elapsed;
+            self.today_focus.sessions += 1;
+            if entry.outcome == SessionOutcome::Completed {
+                self.today_focus.completed += 1;
+            }
+            cx.notify();
+        }
+
+        let history = cx.global::<Database>().history();
+        cx.spawn(async move |this, cx| -> anyhow::Result<()> {
+            history.record(&entry).await?;
+            this.update(cx, |_screen, cx| cx.emit(HistoryChangedEvent))?;
+            return Ok(());
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn timer_area(&mut self, cx: &mut Context<Self>) -> Div {
+        let is_break = self
+            .timer
+            .read(cx)
+            .engine()
+            .session()
+            .is_some_and(|session| session.kind == SessionKind::BREAK);
+
         return div()
             .child(svg().size(px(32.)).text_color(white()).when_else(
-                matches!(current_session.kind, SessionKind::WORK),
+                !is_break,
                 |e| e.path("svg/eye.svg"),
                 |e| e.path("svg/coffee.svg"),
             ))
             .child(self.timer.clone())
+            .when_some(self.suspend_prompt(cx), |el, prompt| el.child(prompt))
+            .when_some(self.finish_prompt(cx), |el, prompt| el.child(prompt))
+            .when_some(self.extend_row(cx), |el, row| el.child(row))
+            .when_some(self.interruption_row(cx), |el, row| el.child(row))
             .flex()
             .gap_2()
             .flex_col()
@@ -90,6 +559,178 @@ impl TimerScreen {
             .items_center();
     }
 
+    /// "More time" buttons for the running session, one per configured increment
+    fn extend_row(&mut self, cx: &mut Context<Self>) -> Option<Div> {
+        let engine = self.timer.read(cx).engine();
+        if !engine.in_session() || engine.counts_up() || self.quick_previous.is_some() {
+            return None;
+        }
+
+        let increments = cx.global::<AppSettings>().extend_increments.clone();
+        return Some(
+            div()
+                .flex()
+                .flex_row()
+                .gap_2()
+                .children(increments.into_iter().map(|minutes| {
+                    Button::new(("extend-session", minutes as usize))
+                        .icon(Icon::new(Icon::empty()).path("icons/plus.svg"))
+                        .label(format!("{minutes} min"))
+                        .ghost()
+                        .small()
+                        .on_click(cx.listener(move |entity, _event, _window, cx| {
+                            let by = Duration::from_millis(ONE_MIN_MS * minutes);
+                            entity.timer.update(cx, |timer, cx| timer.extend(by, cx));
+                        }))
+                })),
+        );
+    }
+
+    /// Log an interruption of the focus session with whatever note was typed, then clear the note
+    fn log_interruption(
+        &mut self,
+        kind: InterruptionKind,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        let note = self.interruption_note.read(cx).value().trim().to_string();
+        let note = (!note.is_empty()).then(|| SharedString::from(note));
+        self.timer
+            .update(cx, |timer, cx| timer.log_interruption(kind, note, cx));
+        self.interruption_note
+            .update(cx, |input, cx| input.set_value("", window, cx));
+        cx.notify();
+    }
+
+    /// Quick actions to log an interruption while a focus session keeps running
+    fn interruption_row(&mut self, cx: &mut Context<Self>) -> Option<Div> {
+        let engine = self.timer.read(cx).engine();
+        let in_focus = engine
+            .session()
+            .is_some_and(|session| session.kind == SessionKind::WORK);
+        // quick runs are not focus sessions of a preset
+        if !engine.in_session() || !in_focus || self.quick_previous.is_some() {
+            return None;
+        }
+
+        let count = |kind: InterruptionKind| {
+            engine
+                .interruptions()
+                .iter()
+                .filter(|interruption| interruption.kind == kind)
+                .count()
+        };
+        let logged = format!(
+            "{} internal, {} external",
+            count(InterruptionKind::Internal),
+            count(InterruptionKind::External)
+        );
+
+        return Some(
+            div()
+                .w_96()
+                .flex()
+                .flex_col()
+                .gap_2()
+                .child(
+                    div()
+                        .flex()
+                        .flex_row()
+                        .items_center()
+                        .gap_2()
+                        .child(div().flex_grow().child(Input::new(&self.interruption_note)))
+                        .child(
+                            Button::new("interruption-internal")
+                                .label("Internal")
+                                .ghost()
+                                .small()
+                                .on_click(cx.listener(|entity, _event, window, cx| {
+                                    entity.log_interruption(InterruptionKind::Internal, window, cx);
+                                })),
+                        )
+                        .child(
+                            Button::new("interruption-external")
+                                .label("External")
+                                .ghost()
+                                .small()
+                                .on_click(cx.listener(|entity, _event, window, cx| {
+                                    entity.log_interruption(InterruptionKind::External, window, cx);
+                                })),
+                        ),
+                )
+                .child(
+                    div()
+                        .text_sm()
+                        .text_color(cx.theme().muted_foreground)
+                        .child(format!("Interruptions: {logged}")),
+                ),
+        );
+    }
+
+    /// Sessions that don't end by themselves, either counting overtime or open ended,
+    /// wait for the user to move on
+    fn finish_prompt(&mut self, cx: &mut Context<Self>) -> Option<Button> {
+        let engine = self.timer.read(cx).engine();
+        let label = if engine.in_overtime() {
+            "Done, move on"
+        } else if engine.in_session() && engine.counts_up() {
+            "End focus"
+        } else {
+            return None;
+        };
+
+        return Some(
+            Button::new("acknowledge-session")
+                .label(label)
+                .primary()
+                .on_click(cx.listener(|entity, _event, _window, cx| {
+                    entity.timer.update(cx, |timer, cx| timer.acknowledge(cx));
+                })),
+        );
+    }
+
+    /// Asks what to do with the time spent asleep, see `SuspendPolicy::Ask`
+    fn suspend_prompt(&mut self, cx: &mut Context<Self>) -> Option<Div> {
+        let gap = self.timer.read(cx).engine().pending_suspend()?;
+        let minutes = gap.as_secs() / 60;
+
+        return Some(
+            div()
+                .flex()
+                .flex_col()
+                .items_center()
+                .gap_2()
+                .text_sm()
+                .child(format!("You were away for {minutes} min"))
+                .child(
+                    div()
+                        .flex()
+                        .flex_row()
+                        .gap_2()
+                        .child(
+                            Button::new("suspend-count")
+                                .label("Count it")
+                                .primary()
+                                .on_click(cx.listener(|entity, _event, _window, cx| {
+                                    entity
+                                        .timer
+                                        .update(cx, |timer, cx| timer.resolve_suspend(true, cx));
+                                })),
+                        )
+                        .child(
+                            Button::new("suspend-ignore")
+                                .label("Don't count it")
+                                .ghost()
+                                .on_click(cx.listener(|entity, _event, _window, cx| {
+                                    entity
+                                        .timer
+                                        .update(cx, |timer, cx| timer.resolve_suspend(false, cx));
+                                })),
+                        ),
+                ),
+        );
+    }
+
     fn button(&mut self, path: &str, cx: &mut Context<Self>) -> Div {
         return div()
             .size_16()
@@ -109,6 +750,24 @@ impl TimerScreen {
             .justify_center();
     }
 
+    fn small_button(&mut self, path: &str, cx: &mut Context<Self>) -> Div {
+        return div()
+            .size_10()
+            .flex_none()
+            .rounded_full()
+            .hover(|el| el.bg(rgb(0x121212)))
+            .child(
+                svg()
+                    .size_5()
+                    .text_color(cx.theme().foreground)
+                    .path(path.to_string()),
+            )
+            .flex()
+            .flex_row()
+            .items_center()
+            .justify_center();
+    }
+
     fn toggle_pause_play(&mut self, cx: &mut Context<Self>) {
         if self.timer.read(cx).is_paused() {
             self.timer.update(cx, |e, cx| {
@@ -127,6 +786,8 @@ impl TimerScreen {
         } else {
             "icons/pause.svg"
         };
+        // out of pauses under the preset's limits
+        let pause_blocked = !self.timer.read(cx).is_paused() && !self.timer.read(cx).can_pause();
 
         return div()
             .flex()
@@ -136,44 +797,122 @@ impl TimerScreen {
             .items_center()
             .justify_around()
             // PAUSE / PLAY
-            .child(self.button(play_pause_icon, cx).on_mouse_up(
+            .child(
+                self.button(play_pause_icon, cx)
+                    .when(pause_blocked, |el| el.opacity(0.4))
+                    .on_mouse_up(
+                        gpui::MouseButton::Left,
+                        cx.listener(|entity, _e, _w, cx| {
+                            entity.toggle_pause_play(cx);
+                        }),
+                    ),
+            )
+            // SKIP BACK
+            .child(self.small_button("icons/skip-back.svg", cx).on_mouse_up(
                 gpui::MouseButton::Left,
                 cx.listener(|entity, _e, _w, cx| {
-                    entity.toggle_pause_play(cx);
+                    entity.timer.update(cx, |entity, cx| {
+                        entity.skip_back(cx);
+                    })
                 }),
             ))
             // timeline
             .child(self.timeline.clone())
-            // STOP
-            .child(self.button("icons/stop.svg", cx).on_mouse_up(
+            // SKIP FORWARD
+            .child(self.small_button("icons/skip-forward.svg", cx).on_mouse_up(
                 gpui::MouseButton::Left,
                 cx.listener(|entity, _e, _w, cx| {
                     entity.timer.update(cx, |entity, cx| {
-                        entity.stop(cx);
+                        entity.skip(cx);
                     })
                 }),
+            ))
+            // STOP
+            .child(self.button("icons/stop.svg", cx).on_mouse_up(
+                gpui::MouseButton::Left,
+                cx.listener(|entity, _e, _w, cx| {
+                    entity.request_stop(cx);
+                }),
             ));
     }
 
-    fn start_timer(&mut self, cx: &mut Context<Self>) {
-        let session = self.session();
-        let preset = &self.preset;
+    fn confirm_abandon_footer(&mut self, cx: &mut Context<Self>) -> Div {
+        let adhoc = self.quick_previous.is_some();
+        let prompt = if adhoc {
+            "Stop this quick run?"
+        } else {
+            "Abandon this focus session?"
+        };
+
+        return div()
+            .p_4()
+            .flex()
+            .flex_col()
+            .items_center()
+            .gap_2()
+            .child(prompt)
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .gap_2()
+                    .child(
+                        Button::new("abandon-stop")
+                            .label("Stop session")
+                            .danger()
+                            .on_click(cx.listener(|entity, _event, _window, cx| {
+                                entity.stop_session(cx);
+                            })),
+                    )
+                    .when(!adhoc, |el| {
+                        el.child(
+                            Button::new("abandon-reset")
+                                .label("Reset preset")
+                                .ghost()
+                                .on_click(cx.listener(|entity, _event, _window, cx| {
+                                    entity.reset_preset(cx);
+                                })),
+                        )
+                    })
+                    .child(
+                        Button::new("abandon-cancel")
+                            .label("Keep going")
+                            .primary()
+                            .on_click(cx.listener(|entity, _event, _window, cx| {
+                                entity.confirming_abandon = false;
+                                cx.notify();
+                            })),
+                    ),
+            );
+    }
 
+    pub fn start_timer(&mut self, cx: &mut Context<Self>) {
+        let preset = self.timer.read(cx).engine().preset().clone();
         self.timeline.update(cx, move |entity, _cx| {
-            entity.update_segments(preset);
+            entity.update_segments(&preset);
         });
 
         self.timer.update(cx, |entity, cx| {
-            entity.start(session, cx);
+            entity.start(cx);
         });
     }
 
     fn idle_footer(&mut self, cx: &mut Context<Self>) -> Div {
+        // stopped part way through the preset, Start carries on from there
+        let engine = self.timer.read(cx).engine();
+        let mid_preset = engine.state() == EngineState::Idle
+            && (engine.session_index() > 0 || engine.cycle() > 0);
+        let label = match engine.session() {
+            Some(session) if mid_preset => format!("Continue ・ {}", session.title),
+            _ => "Start".to_string(),
+        };
+
         return div()
             .p_4()
+            .gap_2()
             .child(
                 div()
-                    .child("Start")
+                    .child(label)
                     .flex_grow()
                     .text_center()
                     .border_1()
@@ -188,23 +927,231 @@ impl TimerScreen {
                         }),
                     ),
             )
+            .when(mid_preset, |el| {
+                el.child(self.small_button("icons/rotate-ccw.svg", cx).on_mouse_up(
+                    gpui::MouseButton::Left,
+                    cx.listener(|entity, _e, _w, cx| {
+                        entity.reset_preset(cx);
+                    }),
+                ))
+            })
             .flex()
             .flex_row()
             .items_center()
             .justify_center();
     }
 
+    /// The last session ended and the next one waits to be started, or counts down to it
+    fn ready_footer(&mut self, cx: &mut Context<Self>) -> Div {
+        let engine = self.timer.read(cx).engine();
+        let title = engine
+            .session()
+            .map(|session| session.title.clone())
+            .unwrap_or_default();
+        let countdown = engine.auto_start_in().map(|left| {
+            let total_seconds = left.as_secs_f32().ceil() as u64;
+            format!(
+                "Starting in {}:{:02}",
+                total_seconds / 60,
+                total_seconds % 60
+            )
+        });
+
+        return div()
+            .p_4()
+            .flex()
+            .flex_col()
+            .items_center()
+            .gap_2()
+            .when_some(countdown, |el, countdown| el.child(countdown))
+            .child(
+                div()
+                    .w_full()
+                    .flex()
+                    .flex_row()
+                    .items_center()
+                    .gap_2()
+                    .child(
+                        div()
+                            .child(format!("Start next ・ {title}"))
+                            .flex_grow()
+                            .text_center()
+                            .rounded_full()
+                            .py_4()
+                            .bg(cx.theme().primary)
+                            .text_color(cx.theme().primary_foreground)
+                            .hover(|el| el.bg(cx.theme().primary_hover))
+                            .on_mouse_up(
+                                gpui::MouseButton::Left,
+                                cx.listener(|entity, _event, _win, cx| {
+                                    entity.start_timer(cx);
+                                }),
+                            ),
+                    )
+                    // SKIP the session that's lined up
+                    .child(self.small_button("icons/skip-forward.svg", cx).on_mouse_up(
+                        gpui::MouseButton::Left,
+                        cx.listener(|entity, _e, _w, cx| {
+                            entity.timer.update(cx, |entity, cx| {
+                                entity.skip(cx);
+                            })
+                        }),
+                    ))
+                    // STOP waiting, Start picks it back up later
+                    .child(self.small_button("icons/stop.svg", cx).on_mouse_up(
+                        gpui::MouseButton::Left,
+                        cx.listener(|entity, _e, _w, cx| {
+                            entity.stop_session(cx);
+                        }),
+                    )),
+            );
+    }
+
+    fn resume_footer(&mut self, cx: &mut Context<Self>) -> Option<Div> {
+        let (preset, saved) = self.pending_resume.as_ref()?;
+        let snapshot = &saved.snapshot;
+        let title = preset
+            .sessions
+            .get(snapshot.session_index)
+            .map(|session| session.title.clone())
+            .unwrap_or_default();
+
+        // a running session kept counting while the app was closed
+        let remaining = match snapshot.deadline {
+            Some(deadline) => deadline
+                .duration_since(SystemTime::now())
+                .unwrap_or(Duration::ZERO),
+            None => snapshot.remaining,
+        };
+        let total_seconds = remaining.as_secs();
+        let left = format!("{:02}:{:02}", total_seconds / 60, total_seconds % 60);
+
+        return Some(
+            div()
+                .p_4()
+                .flex()
+                .flex_col()
+                .items_center()
+                .gap_2()
+                .child(format!("Pick up where you left off? {title}, {left} left"))
+                .child(
+                    div()
+                        .flex()
+                        .flex_row()
+                        .gap_2()
+                        .child(
+                            Button::new("resume-run")
+                                .label("Resume")
+                                .primary()
+                                .on_click(cx.listener(|entity, _event, window, cx| {
+                                    entity.resume_run(window, cx);
+                                })),
+                        )
+                        .child(
+                            Button::new("restart-run")
+                                .label("Restart session")
+                                .ghost()
+                                .on_click(cx.listener(|entity, _event, _window, cx| {
+                                    entity.restart_run(cx);
+                                })),
+                        )
+                        .child(
+                            Button::new("discard-run")
+                                .label("Discard")
+                                .ghost()
+                                .on_click(cx.listener(|entity, _event, _window, cx| {
+                                    entity.discard_run(cx);
+                                })),
+                        ),
+                ),
+        );
+    }
+
+    /// Put a one off preset on the timer and run it, the preset in use comes back afterwards
+    fn start_quick(&mut self, preset: TimerPreset, cx: &mut Context<Self>) {
+        if !self.timer.read(cx).is_idle() {
+            return;
+        }
+
+        if self.quick_previous.is_none() {
+            self.quick_previous = Some(self.timer.read(cx).engine().preset().clone());
+        }
+        self.set_preset(preset, cx);
+        self.start_timer(cx);
+    }
+
+    fn start_quick_timer(&mut self, cx: &mut Context<Self>) {
+        let value = self.quick_duration.read(cx).value();
+        match duration::parse(&value).filter(|d| !d.is_zero()) {
+            Some(length) => {
+                self.quick_error = None;
+                self.start_quick(TimerPreset::quick_timer(length), cx);
+            }
+            None => self.quick_error = Some("Try something like 7m30s".into()),
+        }
+        cx.notify();
+    }
+
+    fn end_quick_run(&mut self, cx: &mut Context<Self>) {
+        if let Some(previous) = self.quick_previous.take() {
+            self.set_preset(previous, cx);
+        }
+    }
+
+    /// Type a duration and go, or run a stopwatch, without touching the presets
+    fn quick_row(&mut self, cx: &mut Context<Self>) -> Div {
+        return div()
+            .px_4()
+            .flex()
+            .flex_row()
+            .items_center()
+            .gap_2()
+            .child(div().flex_grow().child(Input::new(&self.quick_duration)))
+            .child(
+                Button::new("quick-timer-start")
+                    .label("Go")
+                    .ghost()
+                    .on_click(cx.listener(|entity, _event, _window, cx| {
+                        entity.start_quick_timer(cx);
+                    })),
+            )
+            .child(
+                Button::new("stopwatch-start")
+                    .label("Stopwatch")
+                    .ghost()
+                    .on_click(cx.listener(|entity, _event, _window, cx| {
+                        entity.start_quick(TimerPreset::stopwatch(), cx);
+                    })),
+            )
+            .when_some(self.quick_error.clone(), |el, error| {
+                el.child(div().text_sm().text_color(cx.theme().danger).child(error))
+            });
+    }
+
     fn app_container(&mut self, cx: &mut Context<Self>) -> Div {
-        let footer = self.running_footer_row(cx);
-        let idle_footer = self.idle_footer(cx);
+        let footer = if self.confirming_abandon {
+            self.confirm_abandon_footer(cx)
+        } else if self.timer.read(cx).is_ready() {
+            self.ready_footer(cx)
+        } else {
+            self.running_footer_row(cx)
+        };
+        let idle_footer = match self.resume_footer(cx) {
+            Some(resume_footer) => resume_footer,
+            None => div()
+                .flex()
+                .flex_col()
+                .child(self.quick_row(cx))
+                .child(self.idle_footer(cx)),
+        };
 
         return div()
             .flex_grow()
             .flex()
             .flex_col()
-            .child(self.timer_area())
+            .child(self.timer_area(cx))
             .when_else(
-                self.timer.read(cx).is_completed(),
+                self.timer.read(cx).is_idle(),
                 |el| el.child(idle_footer),
                 |el| el.child(footer),
             );
@@ -217,23 +1164,38 @@ impl Render for TimerScreen {
         _window: &mut gpui::Window,
         cx: &mut Context<Self>,
     ) -> impl gpui::IntoElement {
-        let title = format!("Bmo ・ {}", self.preset.title.clone());
+        let title = format!("Bmo ・ {}", self.timer.read(cx).engine().preset().title);
         return div()
             .size_full()
             .flex()
             .flex_col()
             .child(
                 TitleBar::new().child(div().child(title)).child(
-                    div().flex().items_center().gap_2().child(
-                        Button::new("settings")
-                            .icon(IconName::Settings)
-                            .ghost()
-                            .on_click(cx.listener(|_this, _event, _window, cx| {
-                                cx.emit(NavigationEvent {
-                                    screen: Screen::Settings,
-                                });
-                            })),
-                    ),
+                    div()
+                        .flex()
+                        .items_center()
+                        .gap_2()
+                        .children(self.goal_progress(cx))
+                        .child(
+                            Button::new("statistics")
+                                .icon(Icon::new(Icon::empty()).path("icons/chart-column.svg"))
+                                .ghost()
+                                .on_click(cx.listener(|_this, _event, _window, cx| {
+                                    cx.emit(NavigationEvent {
+                                        screen: Screen::Statistics,
+                                    });
+                                })),
+                        )
+                        .child(
+                            Button::new("settings")
+                                .icon(IconName::Settings)
+                                .ghost()
+                                .on_click(cx.listener(|_this, _event, _window, cx| {
+                                    cx.emit(NavigationEvent {
+                                        screen: Screen::Settings,
+                                    });
+                                })),
+                        ),
                 ),
             )
             .child(self.app_container(cx));
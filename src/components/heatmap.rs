@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::{Datelike, Days, NaiveDate};
+use gpui::{
+    Context, Div, InteractiveElement, ParentElement, Render, SharedString, Stateful,
+    StatefulInteractiveElement, Styled, div, prelude::FluentBuilder, px,
+};
+use gpui_component::ActiveTheme;
+use gpui_component::tooltip::Tooltip;
+
+use crate::duration;
+use crate::statistics::{self, DayFocus};
+
+/// Weeks shown, a year and the week it started in
+pub const HEATMAP_WEEKS: u64 = 53;
+
+/// GitHub style calendar of focus per day, a column per week and a row per weekday
+pub struct Heatmap {
+    today: NaiveDate,
+    first_day: NaiveDate, // a Monday, the top of the first column
+    days: HashMap<NaiveDate, DayFocus>,
+    busiest: Duration,
+}
+
+impl Heatmap {
+    pub fn new(today: NaiveDate) -> Self {
+        return Self {
+            today,
+            first_day: Heatmap::first_day(today),
+            days: HashMap::new(),
+            busiest: Duration::ZERO,
+        };
+    }
+
+    /// The Monday the calendar starts on when it ends on `today`
+    pub fn first_day(today: NaiveDate) -> NaiveDate {
+        return statistics::start_of_week(today) - Days::new((HEATMAP_WEEKS - 1) * 7);
+    }
+
+    pub fn set_days(&mut self, today: NaiveDate, days: Vec<DayFocus>, cx: &mut Context<Self>) {
+        self.today = today;
+        self.first_day = Heatmap::first_day(today);
+        self.busiest = days
+            .iter()
+            .map(|day| day.focus)
+            .max()
+            .unwrap_or(Duration::ZERO);
+        self.days = days.into_iter().map(|day| (day.day, day)).collect();
+        cx.notify();
+    }
+
+    /// 0 for no focus, otherwise 1 - 4 by how the day compares to the busiest one
+    fn level(&self, focus: Duration) -> u8 {
+        if focus.is_zero() || self.busiest.is_zero() {
+            return 0;
+        }
+
+        let share = focus.as_secs_f32() / self.busiest.as_secs_f32();
+        return (share * 4.).ceil().clamp(1., 4.) as u8;
+    }
+
+    fn cell(&self, day: NaiveDate, cx: &Context<Self>) -> Stateful<Div> {
+        let focus = self.days.get(&day).copied();
+        let level = self.level(focus.map(|f| f.focus).unwrap_or(Duration::ZERO));
+        let tooltip: SharedString = match focus {
+            Some(focus) => format!(
+                "{}: {}, {} {}",
+                day.format("%a %-d %b %Y"),
+                duration::format_minutes(focus.focus),
+                focus.sessions,
+                if focus.sessions == 1 {
+                    "session"
+                } else {
+                    "sessions"
+                }
+            )
+            .into(),
+            None => format!("{}: no focus", day.format("%a %-d %b %Y")).into(),
+        };
+
+        let theme = cx.theme();
+        let color = match level {
+            0 => theme.border,
+            level => theme.primary.opacity(level as f32 / 4.),
+        };
+
+        return div()
+            .id(("heatmap-day", day.num_days_from_ce() as usize))
+            .size(px(8.))
+            .rounded(px(2.))
+            .bg(color)
+            .tooltip(move |window, cx| Tooltip::new(tooltip.clone()).build(window, cx));
+    }
+}
+
+impl Render for Heatmap {
+    fn render(
+        &mut self,
+        _window: &mut gpui::Window,
+        cx: &mut gpui::Context<Self>,
+    ) -> impl gpui::IntoElement {
+        let weeks: Vec<Div> = (0..HEATMAP_WEEKS)
+            .map(|week| {
+                let monday = self.first_day + Days::new(week * 7);
+                div().flex().flex_col().gap(px(2.)).children(
+                    monday
+                        .iter_days()
+                        .take(7)
+                        .filter(|day| *day <= self.today)
+                        .map(|day| self.cell(day, cx)),
+                )
+            })
+            .collect();
+
+        let total: Duration = self.days.values().map(|day| day.focus).sum();
+
+        return div()
+            .flex()
+            .flex_col()
+            .gap_2()
+            .child(
+                div()
+                    .flex()
+                    .flex_row()
+                    .justify_between()
+                    .text_sm()
+                    .child("Focus over the last year")
+                    .child(
+                        div()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(duration::format_minutes(total)),
+                    ),
+            )
+            .child(div().flex().flex_row().gap(px(2.)).children(weeks))
+            .when(self.days.is_empty(), |el| {
+                el.child(
+                    div()
+                        .text_xs()
+                        .text_color(cx.theme().muted_foreground)
+                        .child("Nothing recorded yet"),
+                )
+            });
+    }
+}
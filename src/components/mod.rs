@@ -1,2 +1,3 @@
+pub mod heatmap;
 pub mod timeline;
 pub mod timer;
@@ -1,80 +1,89 @@
-use std::collections::hash_map::DefaultHasher;
-use std::hash::{Hash, Hasher};
 use std::time::Duration;
 
-use gpui::{Context, Div, relative, rgba};
-use gpui::{ParentElement, Render, SharedString, Styled, div, prelude::FluentBuilder, rems, rgb};
+use gpui::{
+    Context, EventEmitter, InteractiveElement, Stateful, StatefulInteractiveElement, relative, rgba,
+};
+use gpui::{
+    Div, ParentElement, Render, SharedString, Styled, div, prelude::FluentBuilder, rems, rgb,
+};
 use gpui_component::ActiveTheme;
 
-use crate::session::TimerPreset;
+use crate::session::{PresetRepeat, TimerPreset};
 
 pub struct TimeLineSegment {
     title: SharedString,
     color: u32,
+    open_ended: bool, // counts up, so there's no progress to fill in
+}
+
+/// A segment was clicked, `index` is the session it stands for
+pub struct TimeLineSegmentClickedEvent {
+    pub index: usize,
 }
 
 pub struct TimeLine {
     pub active_index: usize,
     pub current_progress: f32, // current progress in percentage 0 - 1
+    pub cycle: u32,            // pass through a repeating preset, counting from 0
     pub total_duration: Duration,
     pub segments: Vec<TimeLineSegment>,
+    repeat: PresetRepeat,
 }
 
+impl EventEmitter<TimeLineSegmentClickedEvent> for TimeLine {}
+
 impl TimeLine {
     pub fn new() -> Self {
         return Self {
             current_progress: 0.,
             active_index: 0,
+            cycle: 0,
             total_duration: Duration::ZERO,
             segments: vec![],
+            repeat: PresetRepeat::default(),
         };
     }
 
-    /// Generate a random color that complements dark UI themes
-    /// Uses a hash of the title to ensure consistent colors for the same segment
-    fn generate_color_for_segment(title: &str, index: usize) -> u32 {
-        let mut hasher = DefaultHasher::new();
-        title.hash(&mut hasher);
-        index.hash(&mut hasher);
-        let hash = hasher.finish();
-
-        // Generate muted colors that work well with dark backgrounds
-        // Keep values in range 30-150 for good contrast without being too bright
-        let r = ((hash >> 0) & 0xFF) as u8;
-        let g = ((hash >> 8) & 0xFF) as u8;
-        let b = ((hash >> 16) & 0xFF) as u8;
-
-        // Map to a darker, more muted range (30-150)
-        let r = 30 + (r as u32 * 120 / 255) as u8;
-        let g = 30 + (g as u32 * 120 / 255) as u8;
-        let b = 30 + (b as u32 * 120 / 255) as u8;
-
-        // Pack into u32 as 0xRRGGBB
-        ((r as u32) << 16) | ((g as u32) << 8) | (b as u32)
-    }
-
     pub fn update_segments(&mut self, preset: &TimerPreset) {
         self.total_duration = preset.total_duration();
+        self.repeat = preset.repeat;
         self.segments = preset
             .sessions
             .iter()
-            .enumerate()
-            .map(|(index, session)| TimeLineSegment {
+            .map(|session| TimeLineSegment {
                 title: session.title.clone(),
-                color: TimeLine::generate_color_for_segment(&session.title, index),
+                color: session.color,
+                open_ended: session.open_ended,
             })
             .collect();
     }
+
+    /// "2/4" for a preset run a set number of times, "#3" for one that loops,
+    /// nothing for a preset that only runs once
+    fn cycle_label(&self) -> Option<SharedString> {
+        let cycle = self.cycle + 1;
+        return match self.repeat {
+            PresetRepeat::Times(1) => None,
+            PresetRepeat::Times(times) => Some(format!("{cycle}/{times}").into()),
+            PresetRepeat::Forever => Some(format!("#{cycle}").into()),
+        };
+    }
 }
 
 fn segment_component(
+    index: usize,
     segment: &TimeLineSegment,
     active: bool,
     percent: f32,
     cx: &mut Context<TimeLine>,
-) -> Div {
+) -> Stateful<Div> {
     return div()
+        .id(("timeline-segment", index))
         .min_w(rems(2.))
+        .cursor_pointer()
+        .on_click(cx.listener(move |_this, _event, _window, cx| {
+            cx.emit(TimeLineSegmentClickedEvent { index });
+        }))
         .when_else(
             active,
             |e| {
@@ -91,6 +100,7 @@ fn segment_component(
                             .justify_center()
                             .text_sm()
                             .child(segment.title.clone().to_uppercase())
+                            .when(segment.open_ended, |el| el.child(" ∞"))
                             .text_color(cx.theme().background),
                     )
                     .flex()
@@ -127,8 +137,19 @@ impl Render for TimeLine {
                     .items_center()
                     .gap_1()
                     .size_full()
+                    .when_some(self.cycle_label(), |el, label| {
+                        el.child(
+                            div()
+                                .flex_none()
+                                .px_1()
+                                .text_sm()
+                                .text_color(cx.theme().muted_foreground)
+                                .child(label),
+                        )
+                    })
                     .children(segments.iter().enumerate().map(|(index, seg)| {
                         segment_component(
+                            index,
                             seg,
                             index == self.active_index,
                             self.current_progress,
@@ -1,72 +1,239 @@
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
-use gpui::{Context, EventEmitter, ParentElement, Render, Styled, Task, div, rems};
-use gpui_component::StyledExt;
+use gpui::prelude::FluentBuilder;
+use gpui::{Context, EventEmitter, ParentElement, Render, SharedString, Styled, Task, div, rems};
+use gpui_component::{ActiveTheme as _, StyledExt};
 
-use crate::session::Session;
+use crate::constants::TICK_INTERVAL_MS;
+use crate::duration::{self, ClockFormat};
+use crate::engine::clock::SystemClock;
+use crate::engine::summary::RunSummary;
+use crate::engine::timer::{EngineEvent, RunSnapshot, SessionRun, SuspendPolicy, TimerEngine};
+use crate::history::InterruptionKind;
+use crate::session::TimerPreset;
 
-pub struct TimerCompletedEvent;
 pub struct TimerTickEvent {
     pub percent_completed: f32,
 }
+pub struct SessionStartedEvent {
+    pub index: usize,
+}
+pub struct SessionEndedEvent {
+    pub run: SessionRun,
+}
+pub struct PresetCompletedEvent {
+    pub summary: RunSummary,
+}
+/// The engine moved to another state or session, anything saving the run should save it again
+pub struct TimerStateChangedEvent;
 
+/// GPUI view over a `TimerEngine`, ticking it while a session runs
+/// and re-emitting what happens as events
 pub struct Timer {
-    pub is_running: bool,
-    pub initial_duration: Duration, // just holding how long we started ccounting down from
-    // the timer countdown, from the duration to 0
-    pub countdown: Duration,
+    engine: TimerEngine<SystemClock>,
     pub timer_task: Option<Task<()>>,
+    clock_format: ClockFormat,
+    show_percent: bool,
 }
 
-impl EventEmitter<TimerCompletedEvent> for Timer {}
 impl EventEmitter<TimerTickEvent> for Timer {}
+impl EventEmitter<SessionStartedEvent> for Timer {}
+impl EventEmitter<SessionEndedEvent> for Timer {}
+impl EventEmitter<PresetCompletedEvent> for Timer {}
+impl EventEmitter<TimerStateChangedEvent> for Timer {}
 
 impl Timer {
-    pub fn new() -> Self {
+    pub fn new(preset: TimerPreset) -> Self {
         return Self {
-            is_running: false,
-            initial_duration: Duration::ZERO,
-            countdown: Duration::ZERO,
+            engine: TimerEngine::new(preset, SystemClock),
             timer_task: None,
+            clock_format: ClockFormat::default(),
+            show_percent: false,
         };
     }
 
-    pub fn is_completed(&self) -> bool {
-        return self.countdown.is_zero();
+    pub fn engine(&self) -> &TimerEngine<SystemClock> {
+        return &self.engine;
+    }
+
+    pub fn is_idle(&self) -> bool {
+        return self.engine.is_idle();
     }
 
-    #[allow(unused)]
     pub fn is_paused(&self) -> bool {
-        return !self.is_completed() && !self.is_running;
+        return self.engine.is_paused();
     }
 
-    pub fn start(&mut self, session: &Session, cx: &mut Context<Timer>) {
-        self.countdown = session.duration.clone();
-        self.initial_duration = session.duration.clone();
-        self.is_running = true;
+    pub fn is_ready(&self) -> bool {
+        return self.engine.is_ready();
+    }
 
-        cx.emit(TimerTickEvent {
-            percent_completed: 0.,
-        });
+    pub fn in_session(&self) -> bool {
+        return self.engine.in_session();
+    }
 
-        self.spawn_timer(cx);
+    pub fn can_pause(&self) -> bool {
+        return self.engine.can_pause();
     }
 
-    pub fn pause(&mut self, cx: &mut Context<Timer>) {
-        self.is_running = false;
-        self.discard_timer();
+    pub fn set_suspend_policy(&mut self, policy: SuspendPolicy) {
+        self.engine.set_suspend_policy(policy);
+    }
+
+    pub fn set_overtime(&mut self, overtime: bool) {
+        self.engine.set_overtime(overtime);
+    }
+
+    /// How the clock is drawn, `show_percent` adds the share of the session left
+    pub fn set_display(&mut self, clock_format: ClockFormat, show_percent: bool) {
+        self.clock_format = clock_format;
+        self.show_percent = show_percent;
+    }
+
+    /// Swap the preset, ignored while a session is in progress
+    pub fn set_preset(&mut self, preset: TimerPreset, cx: &mut Context<Timer>) -> bool {
+        let changed = self.engine.set_preset(preset);
         cx.notify();
+        return changed;
+    }
+
+    pub fn refresh_preset(&mut self, preset: TimerPreset, cx: &mut Context<Timer>) -> bool {
+        let changed = self.engine.refresh_preset(preset);
+        cx.notify();
+        return changed;
+    }
+
+    pub fn start(&mut self, cx: &mut Context<Timer>) {
+        let events = self.engine.start();
+        self.handle_events(events, cx);
+        self.sync_timer(cx);
+    }
+
+    pub fn pause(&mut self, cx: &mut Context<Timer>) {
+        self.engine.pause();
+        self.sync_timer(cx);
     }
 
     pub fn play(&mut self, cx: &mut Context<Timer>) {
-        self.is_running = true;
-        self.spawn_timer(cx);
+        self.engine.resume();
+        self.sync_timer(cx);
     }
 
     pub fn stop(&mut self, cx: &mut Context<Timer>) {
-        self.is_running = false;
-        self.countdown = Duration::ZERO;
+        let events = self.engine.stop();
+        self.handle_events(events, cx);
+        self.sync_timer(cx);
+    }
+
+    pub fn reset(&mut self, cx: &mut Context<Timer>) {
+        let events = self.engine.reset();
+        self.handle_events(events, cx);
+        self.sync_timer(cx);
+    }
+
+    pub fn skip(&mut self, cx: &mut Context<Timer>) {
+        let events = self.engine.skip();
+        self.handle_events(events, cx);
+        self.sync_timer(cx);
+    }
+
+    /// Give the current session `by` more time
+    pub fn extend(&mut self, by: Duration, cx: &mut Context<Timer>) {
+        self.engine.extend(by);
+        cx.emit(TimerTickEvent {
+            percent_completed: self.engine.percent_completed(),
+        });
+        cx.emit(TimerStateChangedEvent);
+        cx.notify();
+    }
+
+    /// Move on from a session that is counting overtime or counting up
+    pub fn acknowledge(&mut self, cx: &mut Context<Timer>) {
+        let events = self.engine.acknowledge();
+        self.handle_events(events, cx);
+        self.sync_timer(cx);
+    }
+
+    /// Note an interruption without stopping the clock
+    pub fn log_interruption(
+        &mut self,
+        kind: InterruptionKind,
+        note: Option<SharedString>,
+        cx: &mut Context<Timer>,
+    ) {
+        self.engine.log_interruption(kind, note);
+        cx.notify();
+    }
+
+    pub fn skip_back(&mut self, cx: &mut Context<Timer>) {
+        let events = self.engine.skip_back();
+        self.handle_events(events, cx);
+        self.sync_timer(cx);
+    }
+
+    pub fn jump_to(&mut self, index: usize, cx: &mut Context<Timer>) {
+        let events = self.engine.jump_to(index);
+        self.handle_events(events, cx);
+        self.sync_timer(cx);
+    }
+
+    /// Pick a saved run back up, see `TimerEngine::restore`
+    pub fn restore(
+        &mut self,
+        preset: TimerPreset,
+        snapshot: &RunSnapshot,
+        cx: &mut Context<Timer>,
+    ) -> bool {
+        let restored = self.engine.restore(preset, snapshot);
+        self.sync_timer(cx);
+        return restored;
+    }
+
+    /// Run a saved run's session again from the top, see `TimerEngine::restart`
+    pub fn restart(
+        &mut self,
+        preset: TimerPreset,
+        snapshot: &RunSnapshot,
+        cx: &mut Context<Timer>,
+    ) {
+        let events = self.engine.restart(preset, snapshot);
+        self.handle_events(events, cx);
+        self.sync_timer(cx);
+    }
+
+    /// Answer the "you were away" prompt, see `TimerEngine::resolve_suspend`
+    pub fn resolve_suspend(&mut self, count_as_elapsed: bool, cx: &mut Context<Timer>) {
+        let events = self.engine.resolve_suspend(count_as_elapsed);
+        self.handle_events(events, cx);
+        self.sync_timer(cx);
+    }
+
+    fn handle_events(&mut self, events: Vec<EngineEvent>, cx: &mut Context<Timer>) {
+        for event in events {
+            match event {
+                EngineEvent::SessionStarted { index } => {
+                    cx.emit(TimerTickEvent {
+                        percent_completed: 0.,
+                    });
+                    cx.emit(SessionStartedEvent { index });
+                }
+                EngineEvent::SessionEnded(run) => cx.emit(SessionEndedEvent { run }),
+                EngineEvent::PresetCompleted(summary) => cx.emit(PresetCompletedEvent { summary }),
+                // the engine is paused with `pending_suspend` set, the screen shows the prompt
+                EngineEvent::SuspendDetected => {}
+            }
+        }
+        cx.notify();
+    }
+
+    /// Restart the tick loop if the engine needs ticks, drop it otherwise.
+    /// Never called from inside the loop, which ends itself once the engine stops
+    fn sync_timer(&mut self, cx: &mut Context<Timer>) {
         self.discard_timer();
+        if self.engine.needs_ticks() {
+            self.spawn_timer(cx);
+        }
+        cx.emit(TimerStateChangedEvent);
         cx.notify();
     }
 
@@ -78,49 +245,37 @@ impl Timer {
 
     fn spawn_timer(&mut self, cx: &mut Context<Timer>) {
         self.timer_task = Some(cx.spawn(async |entity, cx| {
-            let mut time_since_last_loop = Instant::now();
             loop {
                 cx.background_executor()
-                    .timer(Duration::from_millis(200))
+                    .timer(Duration::from_millis(TICK_INTERVAL_MS))
                     .await;
 
-                let time_elasped = Instant::now() - time_since_last_loop;
-                time_since_last_loop = Instant::now();
-
                 let should_continue_loop = entity.update(cx, |entity, cx| {
-                    if !entity.is_running || entity.is_completed() {
+                    if !entity.engine.needs_ticks() {
                         cx.notify();
                         return false;
                     }
 
-                    // tick
-                    if time_elasped > entity.countdown {
-                        entity.countdown = Duration::ZERO
-                    } else {
-                        entity.countdown -= time_elasped;
-                    }
-
                     cx.emit(TimerTickEvent {
-                        percent_completed: (entity.initial_duration.as_secs_f32()
-                            - entity.countdown.as_secs_f32())
-                            / entity.initial_duration.as_secs_f32(),
+                        percent_completed: entity.engine.percent_completed(),
                     });
 
-                    // at this point, if the countdown is zero
-                    // we can call an event
-                    if entity.countdown.is_zero() {
-                        cx.emit(TimerCompletedEvent);
+                    // a pause can end by itself too, see `PauseLimits`
+                    let state = entity.engine.state();
+                    let events = entity.engine.tick();
+                    if !events.is_empty() || entity.engine.state() != state {
+                        entity.handle_events(events, cx);
+                        cx.emit(TimerStateChangedEvent);
                     }
 
                     cx.notify();
-                    return true;
+                    return entity.engine.needs_ticks();
                 });
 
                 if !should_continue_loop.unwrap_or(false) {
                     break;
                 }
             }
-            return ();
         }))
     }
 }
@@ -129,19 +284,71 @@ impl Render for Timer {
     fn render(
         &mut self,
         _window: &mut gpui::Window,
-        _cx: &mut gpui::Context<Self>,
+        cx: &mut gpui::Context<Self>,
     ) -> impl gpui::IntoElement {
-        let total_seconds = self.countdown.as_secs();
+        // past zero the clock counts the overtime up instead, open ended sessions always count up
+        let in_overtime = self.engine.in_overtime();
+        let (sign, total_seconds) = if in_overtime {
+            ("+", self.engine.overtime().as_secs())
+        } else if self.engine.counts_up() {
+            ("", self.engine.elapsed().as_secs())
+        } else {
+            ("", self.engine.remaining().as_secs())
+        };
+
+        let percent_left = (self.show_percent && !in_overtime && !self.engine.counts_up())
+            .then(|| ((1. - self.engine.percent_completed()).clamp(0., 1.) * 100.).round() as u32);
+
+        // time off the clock so far, shown once the session has been paused
+        let pause_count = self.engine.pause_count();
+        let paused = (self.engine.in_session() && pause_count > 0).then(|| {
+            let times = match pause_count {
+                1 => "once".to_string(),
+                count => format!("{count} times"),
+            };
+            format!(
+                "Paused {times}, {}",
+                duration::format_short(self.engine.paused_time())
+            )
+        });
 
-        // format time to string
-        let hour = format!("{:02}", total_seconds / 3600);
-        let minute = format!("{:02}", (total_seconds / 60) % 60);
-        let seconds = format!("{:02}", total_seconds % 60);
+        let clock = div()
+            .flex()
+            .flex_row()
+            .items_baseline()
+            .gap_3()
+            .child(
+                div()
+                    .child(format!(
+                        "{sign}{}",
+                        self.clock_format.format(Duration::from_secs(total_seconds))
+                    ))
+                    .text_size(rems(3.))
+                    .when(in_overtime, |el| el.text_color(cx.theme().warning)),
+            )
+            .when_some(percent_left, |el, percent| {
+                el.child(
+                    div()
+                        .text_size(rems(1.2))
+                        .text_color(cx.theme().muted_foreground)
+                        .child(format!("{percent}%")),
+                )
+            });
 
         return div()
             .font_family("Monaco")
-            .child(format!("{hour}:{minute}:{seconds}"))
-            .text_size(rems(3.))
+            .flex()
+            .flex_col()
+            .items_center()
+            .child(clock)
+            .when_some(paused, |el, paused| {
+                el.child(
+                    div()
+                        .text_sm()
+                        .text_color(cx.theme().muted_foreground)
+                        .child(paused),
+                )
+            })
             .font_medium();
     }
 }
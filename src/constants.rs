@@ -1 +1,13 @@
-pub const ONE_MIN_MS: u64 = 60 * 1 * 1000;
+pub const ONE_MIN_MS: u64 = 60 * 1000;
+
+pub const APP_NAME: &str = "bmo";
+pub const DB_FILE_NAME: &str = "bmo.sqlite";
+
+// how often the timer component ticks the engine
+pub const TICK_INTERVAL_MS: u64 = 200;
+
+// a gap this long between two timer ticks means the machine was asleep
+pub const SUSPEND_GAP_THRESHOLD_MS: u64 = 10 * 1000;
+
+// history label for quick timers and stopwatches that don't belong to a preset
+pub const ADHOC_LABEL: &str = "ad-hoc";
@@ -1,10 +1,21 @@
+use gpui::Global;
 use sqlx::sqlite;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+use crate::constants::{APP_NAME, DB_FILE_NAME};
+use crate::repository::achievements::AchievementRepository;
+use crate::repository::goals::GoalRepository;
+use crate::repository::history::HistoryRepository;
+use crate::repository::presets::PresetRepository;
+use crate::repository::run_state::RunStateRepository;
+use crate::repository::settings::SettingsRepository;
 
 pub struct Database {
     _pool: Option<sqlx::SqlitePool>,
 }
 
+impl Global for Database {}
+
 impl Database {
     pub async fn create_db_pool(path: impl AsRef<Path>) -> Result<sqlx::SqlitePool, sqlx::Error> {
         let options = sqlite::SqliteConnectOptions::new()
@@ -12,6 +23,7 @@ impl Database {
             .optimize_on_close(true, None)
             .synchronous(sqlite::SqliteSynchronous::Normal)
             .journal_mode(sqlite::SqliteJournalMode::Wal)
+            .foreign_keys(true)
             .create_if_missing(true)
             .statement_cache_capacity(0);
 
@@ -20,12 +32,71 @@ impl Database {
         return Ok(pool);
     }
 
+    /// A migrated database that only lives in memory, for tests
+    #[cfg(test)]
+    pub async fn create_memory_pool() -> Result<sqlx::SqlitePool, sqlx::Error> {
+        let options = sqlite::SqliteConnectOptions::new()
+            .in_memory(true)
+            .foreign_keys(true);
+
+        // every connection to an in memory database gets a database of its own
+        let pool = sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(options)
+            .await?;
+        sqlx::migrate!("./migrations").run(&pool).await?;
+        return Ok(pool);
+    }
+
+    /// Open (and migrate) the database at `path`
+    pub async fn connect(path: impl AsRef<Path>) -> Result<Self, sqlx::Error> {
+        if let Some(parent) = path.as_ref().parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let pool = Database::create_db_pool(path).await?;
+        return Ok(Self { _pool: Some(pool) });
+    }
+
+    /// Where the database lives when no explicit path is given.
+    /// `$XDG_DATA_HOME/bmo`, falling back to `~/.local/share/bmo`, then the working directory
+    pub fn default_path() -> PathBuf {
+        let data_dir = std::env::var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".local/share")));
+
+        return match data_dir {
+            Some(dir) => dir.join(APP_NAME).join(DB_FILE_NAME),
+            None => PathBuf::from(DB_FILE_NAME),
+        };
+    }
+
     pub fn pool(&self) -> sqlx::SqlitePool {
         let pref = self._pool.clone();
         return pref.unwrap();
     }
 
-    pub fn new(&self) -> Self {
-        return Self { _pool: None };
+    pub fn presets(&self) -> PresetRepository {
+        return PresetRepository::new(self.pool());
+    }
+
+    pub fn history(&self) -> HistoryRepository {
+        return HistoryRepository::new(self.pool());
+    }
+
+    pub fn run_state(&self) -> RunStateRepository {
+        return RunStateRepository::new(self.pool());
+    }
+
+    pub fn settings(&self) -> SettingsRepository {
+        return SettingsRepository::new(self.pool());
+    }
+
+    pub fn goals(&self) -> GoalRepository {
+        return GoalRepository::new(self.pool());
+    }
+
+    pub fn achievements(&self) -> AchievementRepository {
+        return AchievementRepository::new(self.pool());
     }
 }
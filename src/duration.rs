@@ -0,0 +1,217 @@
+use std::time::Duration;
+
+/// Parse durations typed by hand, like "7m30s", "25m", "1h30m", "90s",
+/// "1:30:00" or "25:00". A bare number is taken as minutes
+pub fn parse(value: &str) -> Option<Duration> {
+    let value = value.trim().to_lowercase();
+    if value.is_empty() {
+        return None;
+    }
+
+    if let Ok(minutes) = value.parse::<u64>() {
+        return Some(Duration::from_secs(minutes.checked_mul(60)?));
+    }
+
+    if value.contains(':') {
+        return parse_clock(&value);
+    }
+
+    let mut total: u64 = 0;
+    let mut number = String::new();
+    for c in value.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'h' | 'm' | 's' => {
+                let amount = number.parse::<u64>().ok()?;
+                number.clear();
+                let seconds = match c {
+                    'h' => amount.checked_mul(3600)?,
+                    'm' => amount.checked_mul(60)?,
+                    _ => amount,
+                };
+                total = seconds.checked_add(total)?;
+            }
+            ' ' => {}
+            _ => return None,
+        }
+    }
+
+    // a trailing number without a unit, as in "1h30"
+    if !number.is_empty() {
+        return None;
+    }
+
+    return Some(Duration::from_secs(total));
+}
+
+/// "1:30:00" as hours, minutes and seconds, "25:00" as minutes and seconds
+fn parse_clock(value: &str) -> Option<Duration> {
+    let parts = value
+        .split(':')
+        .map(|part| part.trim().parse::<u64>().ok())
+        .collect::<Option<Vec<u64>>>()?;
+
+    let (hours, minutes, seconds) = match parts.as_slice() {
+        [minutes, seconds] => (0, *minutes, *seconds),
+        [hours, minutes, seconds] => (*hours, *minutes, *seconds),
+        _ => return None,
+    };
+    if seconds >= 60 || (parts.len() == 3 && minutes >= 60) {
+        return None;
+    }
+
+    let total = hours
+        .checked_mul(3600)?
+        .checked_add(minutes.checked_mul(60)?)?
+        .checked_add(seconds)?;
+    return Some(Duration::from_secs(total));
+}
+
+/// The short form `parse` reads back, like "1h30m", "25m" or "7m30s"
+pub fn format_short(duration: Duration) -> String {
+    let total_seconds = duration.as_secs();
+    let (hours, minutes, seconds) = (
+        total_seconds / 3600,
+        (total_seconds / 60) % 60,
+        total_seconds % 60,
+    );
+
+    let mut text = String::new();
+    if hours > 0 {
+        text.push_str(&format!("{hours}h"));
+    }
+    if minutes > 0 || (hours == 0 && seconds == 0) {
+        text.push_str(&format!("{minutes}m"));
+    }
+    if seconds > 0 {
+        text.push_str(&format!("{seconds}s"));
+    }
+    return text;
+}
+
+/// Whole minutes, with hours split out once there are any, like "25 min" or "1h 05min"
+pub fn format_minutes(duration: Duration) -> String {
+    let minutes = duration.as_secs() / 60;
+    if minutes < 60 {
+        return format!("{minutes} min");
+    }
+    return format!("{}h {:02}min", minutes / 60, minutes % 60);
+}
+
+/// How the timer shows the time it's counting
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ClockFormat {
+    #[default]
+    Full, // HH:MM:SS
+    HideZeroHours, // MM:SS until there's an hour to show
+    Minutes,       // MM:SS, minutes going past 60
+}
+
+impl ClockFormat {
+    /// The value stored in the `settings` table
+    pub fn as_str(&self) -> &'static str {
+        return match self {
+            ClockFormat::Full => "full",
+            ClockFormat::HideZeroHours => "hide_zero_hours",
+            ClockFormat::Minutes => "minutes",
+        };
+    }
+
+    pub fn from_str(value: &str) -> Option<Self> {
+        return match value {
+            "full" => Some(ClockFormat::Full),
+            "hide_zero_hours" => Some(ClockFormat::HideZeroHours),
+            "minutes" => Some(ClockFormat::Minutes),
+            _ => None,
+        };
+    }
+
+    pub fn format(&self, duration: Duration) -> String {
+        let total_seconds = duration.as_secs();
+        let hours = total_seconds / 3600;
+        let minutes = (total_seconds / 60) % 60;
+        let seconds = total_seconds % 60;
+
+        return match self {
+            ClockFormat::Full => format!("{hours:02}:{minutes:02}:{seconds:02}"),
+            ClockFormat::HideZeroHours if hours == 0 => format!("{minutes:02}:{seconds:02}"),
+            ClockFormat::HideZeroHours => format!("{hours}:{minutes:02}:{seconds:02}"),
+            ClockFormat::Minutes => format!("{:02}:{seconds:02}", total_seconds / 60),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn secs(seconds: u64) -> Option<Duration> {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    #[test]
+    fn parse_reads_units_and_bare_minutes() {
+        assert_eq!(parse("25"), secs(25 * 60));
+        assert_eq!(parse("7m30s"), secs(450));
+        assert_eq!(parse("1h30m"), secs(5400));
+        assert_eq!(parse(" 90S "), secs(90));
+        assert_eq!(parse("1h 5m"), secs(3900));
+    }
+
+    #[test]
+    fn parse_rejects_malformed_input() {
+        assert_eq!(parse(""), None);
+        assert_eq!(parse("1h30"), None);
+        assert_eq!(parse("m"), None);
+        assert_eq!(parse("5x"), None);
+    }
+
+    #[test]
+    fn parse_rejects_overflow() {
+        assert_eq!(parse(&u64::MAX.to_string()), None);
+        assert_eq!(parse(&format!("{}h", u64::MAX / 60)), None);
+        assert_eq!(parse(&format!("{}s{}s", u64::MAX, 1)), None);
+        assert_eq!(parse(&format!("{}:00:00", u64::MAX)), None);
+    }
+
+    #[test]
+    fn parse_clock_reads_minutes_or_hours() {
+        assert_eq!(parse("25:00"), secs(1500));
+        assert_eq!(parse("90:00"), secs(5400));
+        assert_eq!(parse("1:30:00"), secs(5400));
+        assert_eq!(parse("1:60:00"), None);
+        assert_eq!(parse("25:60"), None);
+        assert_eq!(parse("1:2:3:4"), None);
+    }
+
+    #[test]
+    fn format_short_reads_back() {
+        assert_eq!(format_short(Duration::ZERO), "0m");
+        assert_eq!(format_short(Duration::from_secs(450)), "7m30s");
+        assert_eq!(format_short(Duration::from_secs(5400)), "1h30m");
+        assert_eq!(format_short(Duration::from_secs(3605)), "1h5s");
+        for seconds in [45, 1500, 3605, 5400, 86_399] {
+            let duration = Duration::from_secs(seconds);
+            assert_eq!(parse(&format_short(duration)), Some(duration));
+        }
+    }
+
+    #[test]
+    fn clock_formats() {
+        let short = Duration::from_secs(25 * 60 + 7);
+        let long = Duration::from_secs(3600 + 5 * 60 + 9);
+        assert_eq!(ClockFormat::Full.format(short), "00:25:07");
+        assert_eq!(ClockFormat::HideZeroHours.format(short), "25:07");
+        assert_eq!(ClockFormat::HideZeroHours.format(long), "1:05:09");
+        assert_eq!(ClockFormat::Minutes.format(long), "65:09");
+
+        for format in [
+            ClockFormat::Full,
+            ClockFormat::HideZeroHours,
+            ClockFormat::Minutes,
+        ] {
+            assert_eq!(ClockFormat::from_str(format.as_str()), Some(format));
+        }
+        assert_eq!(ClockFormat::from_str("seconds"), None);
+    }
+}
@@ -0,0 +1,52 @@
+#[cfg(test)]
+use std::cell::Cell;
+#[cfg(test)]
+use std::rc::Rc;
+#[cfg(test)]
+use std::time::Duration;
+use std::time::SystemTime;
+
+/// Source of time for the timer engine, so it can run against a fake clock.
+/// Wall-clock time is used on purpose: it keeps moving while the machine is asleep,
+/// which is what lets the engine notice a suspend
+pub trait Clock {
+    fn now(&self) -> SystemTime;
+}
+
+/// The real wall clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        return SystemTime::now();
+    }
+}
+
+/// A clock that only moves when told to, for driving the engine in tests.
+/// Clones share the same time, so keep one around to `advance` the engine's copy
+#[cfg(test)]
+#[derive(Debug, Clone)]
+pub struct ManualClock {
+    now: Rc<Cell<SystemTime>>,
+}
+
+#[cfg(test)]
+impl ManualClock {
+    pub fn new() -> Self {
+        return Self {
+            now: Rc::new(Cell::new(SystemTime::now())),
+        };
+    }
+
+    pub fn advance(&self, by: Duration) {
+        self.now.set(self.now.get() + by);
+    }
+}
+
+#[cfg(test)]
+impl Clock for ManualClock {
+    fn now(&self) -> SystemTime {
+        return self.now.get();
+    }
+}
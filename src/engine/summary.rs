@@ -0,0 +1,57 @@
+use std::time::Duration;
+
+use crate::engine::timer::SessionRun;
+use crate::history::{InterruptionKind, SessionOutcome};
+use crate::session::SessionKind;
+
+/// Running totals for one pass through a preset
+#[derive(Debug, Clone, Default)]
+pub struct RunSummary {
+    pub sessions_total: usize,
+    pub completed: usize,
+    pub skipped: usize,
+    pub stopped: usize,
+    pub abandoned: usize,
+    pub focus_time: Duration,
+    pub break_time: Duration,
+    pub extended: Duration,
+    pub overtime: Duration,
+    pub pauses: usize,
+    pub paused: Duration,
+    pub internal_interruptions: usize,
+    pub external_interruptions: usize,
+}
+
+impl RunSummary {
+    pub fn new(sessions_total: usize) -> Self {
+        return Self {
+            sessions_total,
+            ..Default::default()
+        };
+    }
+
+    pub fn record(&mut self, run: &SessionRun) {
+        match run.outcome {
+            SessionOutcome::Completed => self.completed += 1,
+            SessionOutcome::Skipped => self.skipped += 1,
+            SessionOutcome::Stopped => self.stopped += 1,
+            SessionOutcome::Abandoned => self.abandoned += 1,
+        }
+
+        match run.session.kind {
+            SessionKind::WORK => self.focus_time += run.elapsed,
+            SessionKind::BREAK => self.break_time += run.elapsed,
+        }
+
+        self.extended += run.extended;
+        self.overtime += run.overtime;
+        self.pauses += run.pauses.len();
+        self.paused += run.paused();
+        for interruption in &run.interruptions {
+            match interruption.kind {
+                InterruptionKind::Internal => self.internal_interruptions += 1,
+                InterruptionKind::External => self.external_interruptions += 1,
+            }
+        }
+    }
+}
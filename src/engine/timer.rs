@@ -0,0 +1,1544 @@
+use std::time::{Duration, SystemTime};
+
+use gpui::SharedString;
+
+use crate::constants::{SUSPEND_GAP_THRESHOLD_MS, TICK_INTERVAL_MS};
+use crate::engine::clock::Clock;
+use crate::engine::summary::RunSummary;
+use crate::history::{Interruption, InterruptionKind, SessionOutcome};
+use crate::session::{PresetRepeat, Session, SessionKind, TimerPreset};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngineState {
+    Idle,
+    Running,
+    Paused,
+    Ready,     // the next session is lined up, waiting on `start` or its auto start countdown
+    Completed, // every session of the preset has been run
+}
+
+/// What to do with the time the machine spent asleep while a session was running
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SuspendPolicy {
+    #[default]
+    Elapsed, // the session kept counting while asleep
+    Paused, // the session was on hold while asleep
+    Ask,    // pause and let the user pick one of the above
+}
+
+impl SuspendPolicy {
+    /// The value stored in the `settings` table
+    pub fn as_str(&self) -> &'static str {
+        return match self {
+            SuspendPolicy::Elapsed => "elapsed",
+            SuspendPolicy::Paused => "paused",
+            SuspendPolicy::Ask => "ask",
+        };
+    }
+
+    pub fn from_str(value: &str) -> Option<Self> {
+        return match value {
+            "elapsed" => Some(SuspendPolicy::Elapsed),
+            "paused" => Some(SuspendPolicy::Paused),
+            "ask" => Some(SuspendPolicy::Ask),
+            _ => None,
+        };
+    }
+}
+
+/// Enough of a running engine to pick it back up later, see `TimerEngine::restore`
+#[derive(Debug, Clone)]
+pub struct RunSnapshot {
+    pub session_index: usize,
+    pub cycle: u32,
+    pub duration: Duration,
+    pub extended: Duration,  // how much of `duration` was added by `extend`
+    pub remaining: Duration, // time left when the snapshot was taken
+    pub deadline: Option<SystemTime>, // set if the session was running
+    pub overtime: Duration,  // counted past zero before a pause, see `overtime_banked`
+    pub pauses: Vec<PauseInterval>, // finished pauses of the session
+    pub last_flow: Option<Duration>, // sizes a break that follows an open ended session
+}
+
+impl RunSnapshot {
+    /// What was done of the saved session as of the snapshot, ended as stopped.
+    /// None if the session isn't in `preset` anymore
+    pub fn interrupted_run(&self, preset: &TimerPreset) -> Option<SessionRun> {
+        let session = preset.sessions.get(self.session_index)?;
+        return Some(SessionRun {
+            session: session.clone(),
+            planned: self.duration.saturating_sub(self.extended),
+            extended: self.extended,
+            elapsed: self.duration.saturating_sub(self.remaining),
+            overtime: self.overtime,
+            pauses: self.pauses.clone(),
+            interruptions: vec![],
+            outcome: SessionOutcome::Stopped,
+        });
+    }
+}
+
+/// A session that stopped counting, and why
+#[derive(Debug, Clone)]
+pub struct SessionRun {
+    pub session: Session,
+    pub planned: Duration,  // the session's own duration
+    pub extended: Duration, // added on top of `planned` while it ran
+    pub elapsed: Duration,
+    pub overtime: Duration, // counted past zero, see `TimerEngine::set_overtime`
+    pub pauses: Vec<PauseInterval>,
+    pub interruptions: Vec<Interruption>,
+    pub outcome: SessionOutcome,
+}
+
+impl SessionRun {
+    /// Time spent paused over all of the session's pauses
+    pub fn paused(&self) -> Duration {
+        return self.pauses.iter().map(|pause| pause.length).sum();
+    }
+}
+
+/// One stretch a session spent paused
+#[derive(Debug, Clone, Copy)]
+pub struct PauseInterval {
+    pub started_at: SystemTime,
+    pub length: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub enum EngineEvent {
+    SessionStarted { index: usize },
+    SessionEnded(SessionRun),
+    PresetCompleted(RunSummary),
+    SuspendDetected, // only with `SuspendPolicy::Ask`, paused with `pending_suspend` set
+}
+
+/// Runs a preset session by session without knowing anything about the UI.
+/// Time only moves through the `Clock`, and nothing happens between calls,
+/// so the owner has to `tick` it while a session is running
+pub struct TimerEngine<C: Clock> {
+    clock: C,
+    preset: TimerPreset,
+    state: EngineState,
+    session_index: usize,
+    cycle: u32,                       // passes through the preset before this one
+    duration: Duration,               // how long the current session counts down from
+    extended: Duration,               // how much of `duration` was added by `extend`
+    pauses: Vec<PauseInterval>,       // finished pauses of the current session
+    paused_at: Option<SystemTime>,    // when the pause under way started
+    interruptions: Vec<Interruption>, // logged during the current session
+    deadline: Option<SystemTime>,     // when the current session ends, only set while running
+    remaining: Duration,              // time left when not running
+    last_tick: Option<SystemTime>,
+    suspend_policy: SuspendPolicy,
+    overtime: bool,                    // keep counting past zero until `acknowledge`
+    overtime_banked: Duration,         // overtime counted before the session was paused
+    pending_suspend: Option<Duration>, // a sleep gap waiting for the user to decide
+    auto_start_at: Option<SystemTime>, // when a `Ready` session starts by itself
+    last_flow: Option<Duration>,       // how long the last open ended focus session ran
+    summary: RunSummary,               // everything run since the preset was started
+}
+
+impl<C: Clock> TimerEngine<C> {
+    pub fn new(preset: TimerPreset, clock: C) -> Self {
+        return Self {
+            clock,
+            preset,
+            state: EngineState::Idle,
+            session_index: 0,
+            cycle: 0,
+            duration: Duration::ZERO,
+            extended: Duration::ZERO,
+            pauses: vec![],
+            paused_at: None,
+            interruptions: vec![],
+            deadline: None,
+            remaining: Duration::ZERO,
+            last_tick: None,
+            suspend_policy: SuspendPolicy::default(),
+            overtime: false,
+            overtime_banked: Duration::ZERO,
+            pending_suspend: None,
+            auto_start_at: None,
+            last_flow: None,
+            summary: RunSummary::default(),
+        };
+    }
+
+    pub fn set_suspend_policy(&mut self, policy: SuspendPolicy) {
+        self.suspend_policy = policy;
+    }
+
+    /// With overtime on, a session that runs out keeps counting up
+    /// instead of completing, until it is acknowledged
+    pub fn set_overtime(&mut self, overtime: bool) {
+        self.overtime = overtime;
+    }
+
+    /// How long the machine slept, while waiting on `resolve_suspend`
+    pub fn pending_suspend(&self) -> Option<Duration> {
+        return self.pending_suspend;
+    }
+
+    pub fn state(&self) -> EngineState {
+        return self.state;
+    }
+
+    pub fn is_running(&self) -> bool {
+        return self.state == EngineState::Running;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        return self.state == EngineState::Paused;
+    }
+
+    /// Nothing is counting down, either not started yet or the preset is done
+    pub fn is_idle(&self) -> bool {
+        return matches!(self.state, EngineState::Idle | EngineState::Completed);
+    }
+
+    /// Between two sessions, see `EngineState::Ready`
+    pub fn is_ready(&self) -> bool {
+        return self.state == EngineState::Ready;
+    }
+
+    /// A session is under way, running or paused
+    pub fn in_session(&self) -> bool {
+        return matches!(self.state, EngineState::Running | EngineState::Paused);
+    }
+
+    /// Whether the owner has to keep calling `tick`, a pause is ticked to apply its limits
+    pub fn needs_ticks(&self) -> bool {
+        return self.is_running() || self.paused_at.is_some() || self.auto_start_at.is_some();
+    }
+
+    /// Times the current session was paused, counting a pause under way
+    pub fn pause_count(&self) -> usize {
+        return self.pauses.len() + self.paused_at.is_some() as usize;
+    }
+
+    /// Time the current session spent paused, counting a pause under way
+    pub fn paused_time(&self) -> Duration {
+        let ongoing = self
+            .paused_at
+            .map(|at| {
+                self.clock
+                    .now()
+                    .duration_since(at)
+                    .unwrap_or(Duration::ZERO)
+            })
+            .unwrap_or(Duration::ZERO);
+        return self
+            .pauses
+            .iter()
+            .map(|pause| pause.length)
+            .sum::<Duration>()
+            + ongoing;
+    }
+
+    /// Interruptions logged during the current session so far
+    pub fn interruptions(&self) -> &[Interruption] {
+        return &self.interruptions;
+    }
+
+    /// Whether the running session may be paused under the preset's `PauseLimits`
+    pub fn can_pause(&self) -> bool {
+        let limits = self.preset.pause_limits;
+        return self.is_running()
+            && limits
+                .max_pauses
+                .is_none_or(|max| self.pauses.len() < max as usize)
+            && limits.max_paused.is_none_or(|max| self.paused_time() < max);
+    }
+
+    /// Time left before a `Ready` session starts by itself
+    pub fn auto_start_in(&self) -> Option<Duration> {
+        let at = self.auto_start_at?;
+        return Some(
+            at.duration_since(self.clock.now())
+                .unwrap_or(Duration::ZERO),
+        );
+    }
+
+    pub fn preset(&self) -> &TimerPreset {
+        return &self.preset;
+    }
+
+    pub fn session_index(&self) -> usize {
+        return self.session_index;
+    }
+
+    /// Which pass through a repeating preset is running, counting from 0
+    pub fn cycle(&self) -> u32 {
+        return self.cycle;
+    }
+
+    pub fn session(&self) -> Option<&Session> {
+        return self.preset.sessions.get(self.session_index);
+    }
+
+    pub fn remaining(&self) -> Duration {
+        return match self.deadline {
+            Some(deadline) => deadline
+                .duration_since(self.clock.now())
+                .unwrap_or(Duration::ZERO),
+            None => self.remaining,
+        };
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        // an open ended session runs from a deadline at its start, so it is all overtime
+        if self.counts_up() {
+            return self.overtime();
+        }
+
+        return self.duration.saturating_sub(self.remaining());
+    }
+
+    /// The current session is open ended, counting up until it is ended by hand
+    pub fn counts_up(&self) -> bool {
+        return self.session().is_some_and(|session| session.open_ended);
+    }
+
+    /// Time counted since the session ran out
+    pub fn overtime(&self) -> Duration {
+        return match self.deadline {
+            Some(deadline) => self
+                .clock
+                .now()
+                .duration_since(deadline)
+                .unwrap_or(Duration::ZERO),
+            None => self.overtime_banked,
+        };
+    }
+
+    /// The session ran out and is counting up, waiting on `acknowledge`
+    pub fn in_overtime(&self) -> bool {
+        return self.in_session() && !self.duration.is_zero() && self.remaining().is_zero();
+    }
+
+    /// Progress of the current session, 0 - 1
+    pub fn percent_completed(&self) -> f32 {
+        if self.duration.is_zero() {
+            return 0.;
+        }
+
+        return self.elapsed().as_secs_f32() / self.duration.as_secs_f32();
+    }
+
+    /// Replace the preset, only allowed while nothing is running
+    pub fn set_preset(&mut self, preset: TimerPreset) -> bool {
+        if !self.is_idle() {
+            return false;
+        }
+
+        self.preset = preset;
+        self.reset_to(0);
+        self.cycle = 0;
+        self.last_flow = None;
+        self.state = EngineState::Idle;
+        self.summary = self.new_summary();
+        return true;
+    }
+
+    /// Take in a reloaded copy of the current preset. The run keeps its place
+    /// when the sessions are unchanged, otherwise this is `set_preset`
+    pub fn refresh_preset(&mut self, preset: TimerPreset) -> bool {
+        if preset.id != self.preset.id || preset.sessions != self.preset.sessions {
+            return self.set_preset(preset);
+        }
+
+        self.preset = preset;
+        return false;
+    }
+
+    /// Where the current run is at, None when no session is under way
+    pub fn snapshot(&self) -> Option<RunSnapshot> {
+        if !self.in_session() {
+            return None;
+        }
+
+        return Some(RunSnapshot {
+            session_index: self.session_index,
+            cycle: self.cycle,
+            duration: self.duration,
+            extended: self.extended,
+            remaining: self.remaining(),
+            deadline: self.deadline,
+            overtime: self.overtime_banked,
+            pauses: self.pauses.clone(),
+            last_flow: self.last_flow,
+        });
+    }
+
+    /// Load `preset` and carry on with the run described by `snapshot`.
+    /// A running snapshot keeps its deadline, so time that passed since counts as elapsed
+    /// and a session that should have ended in the meantime completes on the next tick.
+    /// A paused one stays paused
+    pub fn restore(&mut self, preset: TimerPreset, snapshot: &RunSnapshot) -> bool {
+        if !self.is_idle() || snapshot.session_index >= preset.sessions.len() {
+            return false;
+        }
+
+        self.preset = preset;
+        self.summary = self.new_summary();
+        self.last_flow = snapshot.last_flow;
+        self.reset_to(snapshot.session_index);
+        self.cycle = snapshot.cycle;
+        self.extended = snapshot.extended;
+        self.duration = snapshot.duration;
+        self.pauses = snapshot.pauses.clone();
+
+        match snapshot.deadline {
+            Some(deadline) => {
+                self.deadline = Some(deadline);
+                self.last_tick = Some(self.clock.now());
+                self.state = EngineState::Running;
+            }
+            None => {
+                // the pause carries on, counted from when the run was picked back up
+                self.remaining = snapshot.remaining.min(snapshot.duration);
+                self.overtime_banked = snapshot.overtime;
+                self.paused_at = Some(self.clock.now());
+                self.state = EngineState::Paused;
+            }
+        }
+
+        return true;
+    }
+
+    /// Load `preset` and run the session `snapshot` was on again from the top,
+    /// what was done of it before is dropped rather than ended
+    pub fn restart(&mut self, preset: TimerPreset, snapshot: &RunSnapshot) -> Vec<EngineEvent> {
+        if !self.is_idle() || snapshot.session_index >= preset.sessions.len() {
+            return vec![];
+        }
+
+        self.preset = preset;
+        self.summary = self.new_summary();
+        self.last_flow = snapshot.last_flow;
+        self.reset_to(snapshot.session_index);
+        self.cycle = snapshot.cycle;
+        return self.begin_session(snapshot.session_index);
+    }
+
+    /// Start the current session from the top.
+    /// Starting a completed preset runs it again from the first session,
+    /// a `Ready` one starts the session that was lined up
+    pub fn start(&mut self) -> Vec<EngineEvent> {
+        if self.in_session() || self.preset.sessions.is_empty() {
+            return vec![];
+        }
+
+        if self.state == EngineState::Completed {
+            self.session_index = 0;
+            self.cycle = 0;
+            self.last_flow = None;
+        }
+
+        // a fresh run of the preset, as opposed to picking a stopped one back up
+        if self.session_index == 0 && self.cycle == 0 {
+            self.summary = self.new_summary();
+        }
+
+        return self.begin_session(self.session_index);
+    }
+
+    pub fn pause(&mut self) {
+        if !self.can_pause() {
+            return;
+        }
+
+        self.overtime_banked = self.overtime();
+        self.remaining = self.remaining();
+        self.deadline = None;
+        self.state = EngineState::Paused;
+        self.paused_at = Some(self.clock.now());
+    }
+
+    pub fn resume(&mut self) {
+        if !self.is_paused() {
+            return;
+        }
+
+        // resuming without answering the prompt keeps the time asleep off the clock
+        self.pending_suspend = None;
+        self.close_pause();
+        self.start_counting();
+    }
+
+    /// Answer a `SuspendDetected` prompt, either taking the time asleep off the session
+    /// or ignoring it, then carry on running
+    pub fn resolve_suspend(&mut self, count_as_elapsed: bool) -> Vec<EngineEvent> {
+        let Some(gap) = self.pending_suspend.take() else {
+            return vec![];
+        };
+
+        if count_as_elapsed {
+            // whatever ran past zero while asleep is overtime, if the session counts it
+            let past_zero = gap.saturating_sub(self.remaining);
+            self.remaining = self.remaining.saturating_sub(gap);
+            if self.overtime || self.counts_up() {
+                self.overtime_banked += past_zero;
+            }
+        }
+
+        self.start_counting();
+        return self.tick();
+    }
+
+    /// Abandon the current session, the preset stays on it so the next `start` runs it again
+    pub fn stop(&mut self) -> Vec<EngineEvent> {
+        if self.is_ready() {
+            self.auto_start_at = None;
+            self.state = EngineState::Idle;
+            return vec![];
+        }
+
+        if self.is_idle() {
+            return vec![];
+        }
+
+        let run = self.end_session(SessionOutcome::Stopped);
+        self.reset_to(self.session_index);
+        self.state = EngineState::Idle;
+        return vec![EngineEvent::SessionEnded(run)];
+    }
+
+    /// Abandon whatever is running and go back to the first session
+    pub fn reset(&mut self) -> Vec<EngineEvent> {
+        let mut events = vec![];
+        if self.in_session() {
+            let run = self.end_session(SessionOutcome::Stopped);
+            events.push(EngineEvent::SessionEnded(run));
+        }
+
+        self.reset_to(0);
+        self.cycle = 0;
+        self.last_flow = None;
+        self.state = EngineState::Idle;
+        self.summary = self.new_summary();
+        return events;
+    }
+
+    /// End the current session early and move on to the next one.
+    /// A session lined up in `Ready` ends as skipped without having run
+    pub fn skip(&mut self) -> Vec<EngineEvent> {
+        if self.is_idle() {
+            return vec![];
+        }
+
+        let run = self.end_session(SessionOutcome::Skipped);
+        let mut events = vec![EngineEvent::SessionEnded(run)];
+        events.extend(self.advance());
+        return events;
+    }
+
+    /// Complete a session that is in overtime or counting up, and move on
+    pub fn acknowledge(&mut self) -> Vec<EngineEvent> {
+        let counting_up = self.in_session() && self.counts_up();
+        if !self.in_overtime() && !counting_up {
+            return vec![];
+        }
+
+        let run = self.end_session(SessionOutcome::Completed);
+        let mut events = vec![EngineEvent::SessionEnded(run)];
+        events.extend(self.advance());
+        return events;
+    }
+
+    /// Note an interruption of the current focus session, the session keeps running
+    pub fn log_interruption(&mut self, kind: InterruptionKind, note: Option<SharedString>) {
+        let in_focus = self
+            .session()
+            .is_some_and(|session| session.kind == SessionKind::WORK);
+        if !self.in_session() || !in_focus {
+            return;
+        }
+
+        self.interruptions.push(Interruption {
+            kind,
+            logged_at: self.clock.now(),
+            note,
+        });
+    }
+
+    /// Add `by` to the current session, the progress is kept relative to the new length.
+    /// A running session's deadline moves, a paused one gets it added to the time left.
+    /// Nothing happens between sessions or while a session counts up
+    pub fn extend(&mut self, by: Duration) {
+        if !self.in_session() || self.counts_up() {
+            return;
+        }
+
+        self.duration += by;
+        self.extended += by;
+        match self.deadline.as_mut() {
+            Some(deadline) => *deadline += by,
+            None => self.remaining += by,
+        }
+    }
+
+    /// Go back to the previous session, or restart the first one.
+    /// On a later pass of a repeating preset the first session goes back into the pass before
+    pub fn skip_back(&mut self) -> Vec<EngineEvent> {
+        if self.is_idle() {
+            return vec![];
+        }
+
+        if self.session_index == 0 && self.cycle > 0 {
+            let last = self.preset.sessions.len() - 1;
+            self.cycle -= 1;
+            return self.jump_to(last);
+        }
+
+        return self.jump_to(self.session_index.saturating_sub(1));
+    }
+
+    /// Start the session at `index`, whatever is running now is ended as skipped.
+    /// Jumping into a completed preset starts a new run of it
+    pub fn jump_to(&mut self, index: usize) -> Vec<EngineEvent> {
+        if index >= self.preset.sessions.len() {
+            return vec![];
+        }
+
+        if self.state == EngineState::Completed {
+            self.cycle = 0;
+            self.last_flow = None;
+            self.summary = self.new_summary();
+        }
+
+        let mut events = vec![];
+        if self.in_session() {
+            let run = self.end_session(SessionOutcome::Skipped);
+            events.push(EngineEvent::SessionEnded(run));
+        }
+
+        events.extend(self.begin_session(index));
+        return events;
+    }
+
+    /// Move past the current session onto the next one, wrapping around
+    /// for another pass of a repeating preset, or complete the preset
+    pub fn advance(&mut self) -> Vec<EngineEvent> {
+        let mut next = self.session_index + 1;
+        if next >= self.preset.sessions.len() {
+            if !self.preset.repeat.has_cycle_after(self.cycle) {
+                self.reset_to(self.session_index);
+                self.state = EngineState::Completed;
+                return vec![EngineEvent::PresetCompleted(self.summary.clone())];
+            }
+
+            self.cycle += 1;
+            next = 0;
+        }
+
+        return self.line_up(next);
+    }
+
+    /// Start the session at `index` straight away if it auto starts without a countdown,
+    /// otherwise hold it in `Ready`
+    fn line_up(&mut self, index: usize) -> Vec<EngineEvent> {
+        let auto_start = self.preset.auto_starts(&self.preset.sessions[index]);
+        let delay = self.preset.auto_start.delay;
+        if auto_start && delay.is_zero() {
+            return self.begin_session(index);
+        }
+
+        self.reset_to(index);
+        self.state = EngineState::Ready;
+        if auto_start {
+            self.auto_start_at = Some(self.clock.now() + delay);
+        }
+        return vec![];
+    }
+
+    /// Check the clock, finishing the current session once it has run out
+    /// or starting a `Ready` one once its countdown is over
+    pub fn tick(&mut self) -> Vec<EngineEvent> {
+        if let Some(at) = self.auto_start_at {
+            if self.clock.now() >= at {
+                return self.begin_session(self.session_index);
+            }
+            return vec![];
+        }
+
+        if self.is_paused() {
+            return self.check_pause_limits();
+        }
+
+        if !self.is_running() {
+            return vec![];
+        }
+
+        if let Some(event) = self.check_suspend() {
+            return vec![event];
+        }
+
+        if !self.remaining().is_zero() || self.overtime || self.counts_up() {
+            return vec![];
+        }
+
+        let run = self.end_session(SessionOutcome::Completed);
+        let mut events = vec![EngineEvent::SessionEnded(run)];
+        events.extend(self.advance());
+        return events;
+    }
+
+    /// Abandon a focus session that stayed paused too long in one go,
+    /// or resume one that used up the time it may spend paused
+    fn check_pause_limits(&mut self) -> Vec<EngineEvent> {
+        let Some(paused_at) = self.paused_at else {
+            return vec![];
+        };
+
+        let limits = self.preset.pause_limits;
+        let this_pause = self
+            .clock
+            .now()
+            .duration_since(paused_at)
+            .unwrap_or(Duration::ZERO);
+        let in_focus = self
+            .session()
+            .is_some_and(|session| session.kind == SessionKind::WORK);
+
+        if in_focus
+            && limits
+                .abandon_after
+                .is_some_and(|after| this_pause >= after)
+        {
+            let run = self.end_session(SessionOutcome::Abandoned);
+            self.reset_to(self.session_index);
+            self.state = EngineState::Idle;
+            return vec![EngineEvent::SessionEnded(run)];
+        }
+
+        if limits
+            .max_paused
+            .is_some_and(|max| self.paused_time() >= max)
+        {
+            self.resume();
+        }
+        return vec![];
+    }
+
+    /// Move the pause under way, if any, into the session's list of pauses
+    fn close_pause(&mut self) {
+        if let Some(started_at) = self.paused_at.take() {
+            let length = self
+                .clock
+                .now()
+                .duration_since(started_at)
+                .unwrap_or(Duration::ZERO);
+            self.pauses.push(PauseInterval { started_at, length });
+        }
+    }
+
+    /// A long silence since the last tick means the machine was asleep,
+    /// apply the suspend policy to it
+    fn check_suspend(&mut self) -> Option<EngineEvent> {
+        let now = self.clock.now();
+        let last_tick = self.last_tick.replace(now)?;
+        let gap = now.duration_since(last_tick).unwrap_or(Duration::ZERO);
+        if gap < Duration::from_millis(SUSPEND_GAP_THRESHOLD_MS) {
+            return None;
+        }
+
+        let deadline = self.deadline?;
+        match self.suspend_policy {
+            SuspendPolicy::Elapsed => return None,
+            SuspendPolicy::Paused => {
+                // one tick's worth of the gap was spent awake
+                let asleep = gap.saturating_sub(Duration::from_millis(TICK_INTERVAL_MS));
+                self.deadline = Some(deadline + asleep);
+                return None;
+            }
+            SuspendPolicy::Ask => {
+                // hold the session where it was when the machine went to sleep,
+                // keeping the overtime counted by then the way a pause does
+                self.remaining = deadline.duration_since(last_tick).unwrap_or(Duration::ZERO);
+                self.overtime_banked = last_tick.duration_since(deadline).unwrap_or(Duration::ZERO);
+                self.deadline = None;
+                self.state = EngineState::Paused;
+                self.pending_suspend = Some(gap);
+                return Some(EngineEvent::SuspendDetected);
+            }
+        }
+    }
+
+    fn begin_session(&mut self, index: usize) -> Vec<EngineEvent> {
+        self.reset_to(index);
+        self.start_counting();
+        return vec![EngineEvent::SessionStarted { index }];
+    }
+
+    /// Run down `remaining` from now on, overtime carries on from where it was
+    fn start_counting(&mut self) {
+        let now = self.clock.now();
+        let deadline = now + self.remaining;
+        self.deadline = Some(
+            deadline
+                .checked_sub(self.overtime_banked)
+                .unwrap_or(deadline),
+        );
+        self.overtime_banked = Duration::ZERO;
+        self.last_tick = Some(now);
+        self.state = EngineState::Running;
+    }
+
+    fn end_session(&mut self, outcome: SessionOutcome) -> SessionRun {
+        self.close_pause();
+        let elapsed = self.elapsed();
+        let overtime = if self.in_overtime() {
+            self.overtime()
+        } else {
+            Duration::ZERO
+        };
+        // a session skipped from `Ready` never ran, it doesn't size the next break
+        if self.in_session() && self.counts_up() {
+            self.last_flow = Some(elapsed);
+        }
+        self.remaining = self.remaining();
+        self.deadline = None;
+
+        let run = SessionRun {
+            session: self.session().cloned().expect("running session exists"),
+            planned: self.duration.saturating_sub(self.extended),
+            extended: self.extended,
+            elapsed,
+            overtime,
+            pauses: std::mem::take(&mut self.pauses),
+            interruptions: std::mem::take(&mut self.interruptions),
+            outcome,
+        };
+
+        self.summary.record(&run);
+        return run;
+    }
+
+    /// How long the session at `index` counts down from. A break right after
+    /// an open ended focus session is sized by how long that one ran
+    fn session_duration(&self, index: usize) -> Duration {
+        let sessions = &self.preset.sessions;
+        let Some(session) = sessions.get(index) else {
+            return Duration::ZERO;
+        };
+
+        if session.open_ended {
+            return Duration::ZERO;
+        }
+
+        let previous = index.checked_sub(1).unwrap_or(sessions.len() - 1);
+        let after_flow = session.kind == SessionKind::BREAK && sessions[previous].open_ended;
+        return match self.last_flow {
+            Some(flow) if after_flow => self.preset.flow_breaks.break_for(flow),
+            _ => session.duration,
+        };
+    }
+
+    /// An empty summary sized for every pass the preset will make
+    fn new_summary(&self) -> RunSummary {
+        let cycles = match self.preset.repeat {
+            PresetRepeat::Times(times) => times.max(1) as usize,
+            PresetRepeat::Forever => 1,
+        };
+        return RunSummary::new(self.preset.sessions.len() * cycles);
+    }
+
+    /// Point at `index` with its full duration, not running
+    fn reset_to(&mut self, index: usize) {
+        self.session_index = index;
+        self.duration = self.session_duration(index);
+        self.remaining = self.duration;
+        self.extended = Duration::ZERO;
+        self.pauses.clear();
+        self.interruptions.clear();
+        self.paused_at = None;
+        self.overtime_banked = Duration::ZERO;
+        self.deadline = None;
+        self.last_tick = None;
+        self.pending_suspend = None;
+        self.auto_start_at = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+    use crate::engine::clock::ManualClock;
+    use crate::history::HistoryEntry;
+
+    const MIN: Duration = Duration::from_secs(60);
+
+    /// 25 minutes of focus and a 5 minute break, starting each other by default
+    fn preset() -> TimerPreset {
+        return TimerPreset {
+            rule: None,
+            sessions: vec![
+                Session::new("Focus".into(), 25 * MIN, SessionKind::WORK),
+                Session::new("Break".into(), 5 * MIN, SessionKind::BREAK),
+            ],
+            ..TimerPreset::default()
+        };
+    }
+
+    fn engine(preset: TimerPreset) -> (TimerEngine<ManualClock>, ManualClock) {
+        let clock = ManualClock::new();
+        return (TimerEngine::new(preset, clock.clone()), clock);
+    }
+
+    /// An engine sharing `clock` with another one, to pick up that one's snapshot
+    fn engine_with_clock(
+        preset: TimerPreset,
+        clock: ManualClock,
+    ) -> (TimerEngine<ManualClock>, ManualClock) {
+        return (TimerEngine::new(preset, clock.clone()), clock);
+    }
+
+    fn ended(events: &[EngineEvent]) -> Vec<&SessionRun> {
+        return events
+            .iter()
+            .filter_map(|event| match event {
+                EngineEvent::SessionEnded(run) => Some(run),
+                _ => None,
+            })
+            .collect();
+    }
+
+    #[test]
+    fn start_runs_the_first_session() {
+        let (mut engine, clock) = engine(preset());
+
+        let events = engine.start();
+        assert!(matches!(
+            events[..],
+            [EngineEvent::SessionStarted { index: 0 }]
+        ));
+        assert!(engine.is_running());
+
+        clock.advance(10 * MIN);
+        assert_eq!(engine.remaining(), 15 * MIN);
+        assert_eq!(engine.elapsed(), 10 * MIN);
+        assert!(engine.tick().is_empty());
+    }
+
+    #[test]
+    fn pause_holds_the_clock_until_resumed() {
+        let (mut engine, clock) = engine(preset());
+        engine.start();
+        clock.advance(5 * MIN);
+
+        engine.pause();
+        assert!(engine.is_paused());
+        clock.advance(10 * MIN);
+        assert_eq!(engine.remaining(), 20 * MIN);
+        assert_eq!(engine.paused_time(), 10 * MIN);
+        assert!(engine.tick().is_empty());
+
+        engine.resume();
+        clock.advance(5 * MIN);
+        assert!(engine.is_running());
+        assert_eq!(engine.remaining(), 15 * MIN);
+        assert_eq!(engine.pause_count(), 1);
+
+        let events = engine.skip();
+        let run = ended(&events)[0];
+        assert_eq!(run.elapsed, 10 * MIN);
+        assert_eq!(run.paused(), 10 * MIN);
+    }
+
+    #[test]
+    fn skip_ends_the_session_and_starts_the_next() {
+        let (mut engine, clock) = engine(preset());
+        engine.start();
+        clock.advance(MIN);
+
+        let events = engine.skip();
+        let runs = ended(&events);
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].outcome, SessionOutcome::Skipped);
+        assert_eq!(runs[0].elapsed, MIN);
+        assert!(matches!(
+            events.last(),
+            Some(EngineEvent::SessionStarted { index: 1 })
+        ));
+        assert_eq!(engine.session_index(), 1);
+        assert_eq!(engine.remaining(), 5 * MIN);
+    }
+
+    #[test]
+    fn session_without_auto_start_waits_in_ready() {
+        let mut preset = preset();
+        preset.auto_start.breaks = false;
+        let (mut engine, clock) = engine(preset);
+        engine.start();
+        clock.advance(25 * MIN);
+
+        let events = engine.tick();
+        assert_eq!(ended(&events)[0].outcome, SessionOutcome::Completed);
+        assert!(engine.is_ready());
+        assert_eq!(engine.session_index(), 1);
+
+        assert!(matches!(
+            engine.start()[..],
+            [EngineEvent::SessionStarted { index: 1 }]
+        ));
+    }
+
+    #[test]
+    fn overtime_counts_past_zero_until_acknowledged() {
+        let (mut engine, clock) = engine(preset());
+        engine.set_overtime(true);
+        engine.start();
+        clock.advance(26 * MIN);
+
+        assert!(engine.tick().is_empty());
+        assert!(engine.in_overtime());
+        assert_eq!(engine.overtime(), MIN);
+
+        // a pause keeps the overtime counted so far
+        engine.pause();
+        clock.advance(5 * MIN);
+        assert_eq!(engine.overtime(), MIN);
+        engine.resume();
+        clock.advance(MIN);
+        assert_eq!(engine.overtime(), 2 * MIN);
+
+        let events = engine.acknowledge();
+        let run = ended(&events)[0];
+        assert_eq!(run.outcome, SessionOutcome::Completed);
+        assert_eq!(run.overtime, 2 * MIN);
+        assert_eq!(engine.session_index(), 1);
+    }
+
+    #[test]
+    fn running_every_session_completes_the_preset() {
+        let (mut engine, clock) = engine(preset());
+        engine.start();
+        clock.advance(25 * MIN);
+        engine.tick();
+        clock.advance(5 * MIN);
+
+        let events = engine.tick();
+        let Some(EngineEvent::PresetCompleted(summary)) = events.last() else {
+            panic!("expected the preset to complete, got {events:?}");
+        };
+        assert_eq!(summary.sessions_total, 2);
+        assert_eq!(summary.completed, 2);
+        assert_eq!(summary.focus_time, 25 * MIN);
+        assert_eq!(summary.break_time, 5 * MIN);
+        assert_eq!(engine.state(), EngineState::Completed);
+        assert!(engine.is_idle());
+
+        // starting again runs the preset from the top
+        assert!(matches!(
+            engine.start()[..],
+            [EngineEvent::SessionStarted { index: 0 }]
+        ));
+    }
+
+    #[test]
+    fn paused_policy_holds_the_time_asleep() {
+        let tick = Duration::from_millis(TICK_INTERVAL_MS);
+        let (mut engine, clock) = engine(preset());
+        engine.set_suspend_policy(SuspendPolicy::Paused);
+        engine.start();
+        clock.advance(tick);
+        engine.tick();
+
+        clock.advance(60 * MIN);
+        assert!(engine.tick().is_empty());
+        assert_eq!(engine.remaining(), 25 * MIN - 2 * tick);
+    }
+
+    #[test]
+    fn ask_policy_keeps_the_overtime() {
+        let (mut engine, clock) = engine(preset());
+        engine.set_overtime(true);
+        engine.start();
+        clock.advance(26 * MIN);
+        engine.tick();
+
+        engine.set_suspend_policy(SuspendPolicy::Ask);
+        clock.advance(60 * MIN);
+        assert!(matches!(engine.tick()[..], [EngineEvent::SuspendDetected]));
+        assert!(engine.is_paused());
+        assert_eq!(engine.pending_suspend(), Some(60 * MIN));
+        assert_eq!(engine.overtime(), MIN);
+
+        engine.resolve_suspend(false);
+        clock.advance(MIN);
+        assert_eq!(engine.overtime(), 2 * MIN);
+    }
+
+    #[test]
+    fn ask_policy_counts_the_time_asleep_as_overtime() {
+        let (mut engine, clock) = engine(preset());
+        engine.set_overtime(true);
+        engine.set_suspend_policy(SuspendPolicy::Ask);
+        engine.start();
+        clock.advance(5 * MIN);
+        engine.tick();
+
+        engine.resolve_suspend(true);
+        assert_eq!(engine.remaining(), 20 * MIN);
+        assert_eq!(engine.overtime(), Duration::ZERO);
+
+        clock.advance(60 * MIN);
+        engine.tick();
+        engine.resolve_suspend(true);
+        assert!(engine.in_overtime());
+        assert_eq!(engine.overtime(), 40 * MIN);
+    }
+
+    #[test]
+    fn restart_runs_the_saved_session_again_without_ending_it() {
+        let (mut engine, _clock) = engine(preset());
+        let snapshot = RunSnapshot {
+            session_index: 1,
+            cycle: 0,
+            duration: 5 * MIN,
+            extended: Duration::ZERO,
+            remaining: 2 * MIN,
+            deadline: None,
+            overtime: Duration::ZERO,
+            pauses: vec![],
+            last_flow: None,
+        };
+
+        let events = engine.restart(preset(), &snapshot);
+        assert!(matches!(
+            events[..],
+            [EngineEvent::SessionStarted { index: 1 }]
+        ));
+        assert!(engine.in_session());
+        assert_eq!(engine.remaining(), 5 * MIN);
+    }
+
+    #[test]
+    fn repeating_preset_wraps_around() {
+        let mut preset = preset();
+        preset.repeat = PresetRepeat::Times(2);
+        let (mut engine, clock) = engine(preset);
+        engine.start();
+        clock.advance(25 * MIN);
+        engine.tick();
+        clock.advance(5 * MIN);
+
+        let events = engine.tick();
+        assert!(matches!(
+            events.last(),
+            Some(EngineEvent::SessionStarted { index: 0 })
+        ));
+        assert_eq!(engine.cycle(), 1);
+    }
+
+    #[test]
+    fn skip_back_from_the_first_session_goes_into_the_previous_cycle() {
+        let mut preset = preset();
+        preset.repeat = PresetRepeat::Times(2);
+        let (mut engine, clock) = engine(preset);
+        engine.start();
+        engine.skip();
+        engine.skip();
+        assert_eq!((engine.cycle(), engine.session_index()), (1, 0));
+        clock.advance(MIN);
+
+        let events = engine.skip_back();
+        assert_eq!(ended(&events)[0].outcome, SessionOutcome::Skipped);
+        assert!(matches!(
+            events.last(),
+            Some(EngineEvent::SessionStarted { index: 1 })
+        ));
+        assert_eq!((engine.cycle(), engine.session_index()), (0, 1));
+        assert_eq!(engine.remaining(), 5 * MIN);
+
+        // on the first pass the first session only starts over
+        engine.skip_back();
+        clock.advance(MIN);
+        engine.skip_back();
+        assert_eq!((engine.cycle(), engine.session_index()), (0, 0));
+        assert_eq!(engine.remaining(), 25 * MIN);
+    }
+
+    #[test]
+    fn jump_out_of_range_changes_nothing() {
+        let (mut engine, clock) = engine(preset());
+        engine.start();
+        clock.advance(MIN);
+
+        assert!(engine.jump_to(2).is_empty());
+        assert!(engine.is_running());
+        assert_eq!(engine.session_index(), 0);
+        assert_eq!(engine.remaining(), 24 * MIN);
+    }
+
+    #[test]
+    fn jump_ends_a_running_or_paused_session_as_skipped() {
+        let (mut engine, clock) = engine(preset());
+        engine.start();
+        clock.advance(MIN);
+
+        let events = engine.jump_to(1);
+        let run = ended(&events)[0];
+        assert_eq!(run.outcome, SessionOutcome::Skipped);
+        assert_eq!(run.elapsed, MIN);
+        assert!(matches!(
+            events.last(),
+            Some(EngineEvent::SessionStarted { index: 1 })
+        ));
+        assert!(engine.is_running());
+
+        clock.advance(MIN);
+        engine.pause();
+        clock.advance(3 * MIN);
+        let events = engine.jump_to(0);
+        let run = ended(&events)[0];
+        assert_eq!(run.outcome, SessionOutcome::Skipped);
+        assert_eq!(run.elapsed, MIN);
+        assert_eq!(run.paused(), 3 * MIN);
+        assert!(engine.is_running());
+        assert_eq!(engine.session_index(), 0);
+        assert_eq!(engine.remaining(), 25 * MIN);
+    }
+
+    #[test]
+    fn extend_moves_the_deadline_and_is_recorded() {
+        let (mut engine, clock) = engine(preset());
+        engine.start();
+        clock.advance(10 * MIN);
+
+        engine.extend(5 * MIN);
+        assert_eq!(engine.remaining(), 20 * MIN);
+        clock.advance(15 * MIN);
+        assert!(engine.tick().is_empty());
+        clock.advance(5 * MIN);
+
+        let events = engine.tick();
+        let run = ended(&events)[0];
+        assert_eq!(run.outcome, SessionOutcome::Completed);
+        assert_eq!(run.planned, 25 * MIN);
+        assert_eq!(run.extended, 5 * MIN);
+        assert_eq!(run.elapsed, 30 * MIN);
+
+        let entry = HistoryEntry::from_run(run, None, Utc::now(), Utc::now());
+        assert_eq!(entry.planned, 25 * MIN);
+        assert_eq!(entry.extended, 5 * MIN);
+
+        let events = engine.skip();
+        let Some(EngineEvent::PresetCompleted(summary)) = events.last() else {
+            panic!("expected the preset to complete, got {events:?}");
+        };
+        assert_eq!(summary.extended, 5 * MIN);
+        assert_eq!(summary.focus_time, 30 * MIN);
+    }
+
+    #[test]
+    fn extend_while_paused_adds_to_the_time_left() {
+        let (mut engine, clock) = engine(preset());
+        engine.start();
+        clock.advance(10 * MIN);
+        engine.pause();
+
+        engine.extend(5 * MIN);
+        assert!(engine.is_paused());
+        assert_eq!(engine.remaining(), 20 * MIN);
+
+        clock.advance(MIN);
+        engine.resume();
+        clock.advance(MIN);
+        assert_eq!(engine.remaining(), 19 * MIN);
+    }
+
+    #[test]
+    fn extend_between_sessions_does_nothing() {
+        let mut preset = preset();
+        preset.auto_start.breaks = false;
+        let (mut engine, clock) = engine(preset);
+        engine.start();
+        clock.advance(25 * MIN);
+        engine.tick();
+        assert!(engine.is_ready());
+
+        engine.extend(5 * MIN);
+        assert!(engine.is_ready());
+        assert_eq!(engine.remaining(), 5 * MIN);
+
+        let events = engine.start();
+        assert!(matches!(
+            events[..],
+            [EngineEvent::SessionStarted { index: 1 }]
+        ));
+        assert_eq!(
+            ended(&engine.skip()).first().map(|run| run.extended),
+            Some(Duration::ZERO)
+        );
+    }
+
+    #[test]
+    fn break_after_an_open_ended_session_is_sized_by_the_flow() {
+        let mut flow = Session::new("Flow".into(), Duration::ZERO, SessionKind::WORK);
+        flow.open_ended = true;
+        let mut preset = preset();
+        preset.sessions[0] = flow;
+        let (mut engine, clock) = engine(preset);
+        engine.start();
+        clock.advance(40 * MIN);
+        assert!(engine.tick().is_empty());
+        assert_eq!(engine.elapsed(), 40 * MIN);
+
+        let events = engine.acknowledge();
+        assert_eq!(ended(&events)[0].elapsed, 40 * MIN);
+        assert_eq!(engine.session_index(), 1);
+        // 40 minutes falls in the default "50=8" bracket
+        assert_eq!(engine.remaining(), 8 * MIN);
+    }
+
+    #[test]
+    fn max_pauses_stops_further_pauses() {
+        let mut preset = preset();
+        preset.pause_limits.max_pauses = Some(1);
+        let (mut engine, clock) = engine(preset);
+        engine.start();
+        assert!(engine.can_pause());
+
+        engine.pause();
+        clock.advance(MIN);
+        engine.resume();
+        assert!(!engine.can_pause());
+
+        engine.pause();
+        assert!(engine.is_running());
+        assert_eq!(engine.pause_count(), 1);
+
+        // the limit is per session
+        engine.skip();
+        assert!(engine.can_pause());
+    }
+
+    #[test]
+    fn max_paused_resumes_by_itself() {
+        let mut preset = preset();
+        preset.pause_limits.max_paused = Some(5 * MIN);
+        let (mut engine, clock) = engine(preset);
+        engine.start();
+        clock.advance(MIN);
+        engine.pause();
+        assert!(engine.needs_ticks());
+
+        clock.advance(4 * MIN);
+        assert!(engine.tick().is_empty());
+        assert!(engine.is_paused());
+
+        clock.advance(MIN);
+        assert!(engine.tick().is_empty());
+        assert!(engine.is_running());
+        assert_eq!(engine.paused_time(), 5 * MIN);
+        assert_eq!(engine.remaining(), 24 * MIN);
+        // the time allowed paused is used up
+        assert!(!engine.can_pause());
+    }
+
+    #[test]
+    fn focus_paused_past_abandon_after_is_abandoned() {
+        let mut preset = preset();
+        preset.pause_limits.abandon_after = Some(10 * MIN);
+        let (mut engine, clock) = engine(preset);
+        engine.start();
+        clock.advance(3 * MIN);
+        engine.pause();
+
+        clock.advance(9 * MIN);
+        assert!(engine.tick().is_empty());
+        clock.advance(MIN);
+
+        let events = engine.tick();
+        assert_eq!(events.len(), 1);
+        let run = ended(&events)[0];
+        assert_eq!(run.outcome, SessionOutcome::Abandoned);
+        assert_eq!(run.elapsed, 3 * MIN);
+        assert_eq!(run.paused(), 10 * MIN);
+        assert_eq!(engine.state(), EngineState::Idle);
+        assert_eq!(engine.session_index(), 0);
+        assert_eq!(engine.remaining(), 25 * MIN);
+    }
+
+    #[test]
+    fn break_paused_past_abandon_after_stays_paused() {
+        let mut preset = preset();
+        preset.pause_limits.abandon_after = Some(10 * MIN);
+        let (mut engine, clock) = engine(preset);
+        engine.start();
+        engine.skip();
+        engine.pause();
+
+        clock.advance(30 * MIN);
+        assert!(engine.tick().is_empty());
+        assert!(engine.is_paused());
+    }
+
+    #[test]
+    fn restore_keeps_a_flow_sized_break_as_planned() {
+        let mut flow = Session::new("Flow".into(), Duration::ZERO, SessionKind::WORK);
+        flow.open_ended = true;
+        let mut preset = preset();
+        preset.sessions[0] = flow;
+        let (mut engine, clock) = engine(preset.clone());
+        engine.start();
+        clock.advance(40 * MIN);
+        engine.acknowledge();
+        clock.advance(MIN);
+        let snapshot = engine.snapshot().unwrap();
+
+        let (mut restored, clock) = engine_with_clock(preset, clock);
+        assert!(restored.restore(restored.preset().clone(), &snapshot));
+        assert_eq!(restored.remaining(), 7 * MIN);
+
+        clock.advance(7 * MIN);
+        let events = restored.tick();
+        let run = ended(&events)[0];
+        assert_eq!(run.planned, 8 * MIN);
+        assert_eq!(run.extended, Duration::ZERO);
+    }
+
+    #[test]
+    fn restore_keeps_the_pauses_and_overtime_of_a_paused_run() {
+        let (mut engine, clock) = engine(preset());
+        engine.set_overtime(true);
+        engine.start();
+        clock.advance(10 * MIN);
+        engine.pause();
+        clock.advance(2 * MIN);
+        engine.resume();
+        clock.advance(16 * MIN);
+        engine.pause();
+        let snapshot = engine.snapshot().unwrap();
+        assert_eq!(snapshot.overtime, MIN);
+
+        let (mut restored, clock) = engine_with_clock(preset(), clock);
+        restored.set_overtime(true);
+        assert!(restored.restore(preset(), &snapshot));
+        assert!(restored.is_paused());
+        assert!(restored.in_overtime());
+        assert_eq!(restored.overtime(), MIN);
+        assert_eq!(restored.pause_count(), 2);
+        assert_eq!(restored.paused_time(), 2 * MIN);
+
+        clock.advance(MIN);
+        restored.resume();
+        clock.advance(MIN);
+        assert_eq!(restored.overtime(), 2 * MIN);
+        let events = restored.acknowledge();
+        let run = ended(&events)[0];
+        assert_eq!(run.overtime, 2 * MIN);
+        assert_eq!(run.pauses.len(), 2);
+        assert_eq!(run.paused(), 3 * MIN);
+    }
+
+    #[test]
+    fn interrupted_run_keeps_what_the_snapshot_had_done() {
+        let (mut engine, clock) = engine(preset());
+        engine.start();
+        clock.advance(10 * MIN);
+        engine.extend(5 * MIN);
+        engine.pause();
+        let snapshot = engine.snapshot().unwrap();
+
+        let run = snapshot.interrupted_run(&preset()).unwrap();
+        assert_eq!(run.outcome, SessionOutcome::Stopped);
+        assert_eq!(run.session.title.as_ref(), "Focus");
+        assert_eq!(run.planned, 25 * MIN);
+        assert_eq!(run.extended, 5 * MIN);
+        assert_eq!(run.elapsed, 10 * MIN);
+
+        let emptied = TimerPreset {
+            sessions: vec![],
+            ..preset()
+        };
+        assert!(snapshot.interrupted_run(&emptied).is_none());
+    }
+
+    #[test]
+    fn skip_from_ready_ends_the_lined_up_session_as_skipped() {
+        let mut preset = preset();
+        preset.auto_start.breaks = false;
+        let (mut engine, clock) = engine(preset);
+        engine.start();
+        clock.advance(25 * MIN);
+        engine.tick();
+        assert!(engine.is_ready());
+
+        let events = engine.skip();
+        let run = ended(&events)[0];
+        assert_eq!(run.outcome, SessionOutcome::Skipped);
+        assert_eq!(run.session.title.as_ref(), "Break");
+        assert_eq!(run.elapsed, Duration::ZERO);
+        let Some(EngineEvent::PresetCompleted(summary)) = events.last() else {
+            panic!("expected the preset to complete, got {events:?}");
+        };
+        assert_eq!(summary.completed, 1);
+        assert_eq!(summary.skipped, 1);
+        assert_eq!(summary.break_time, Duration::ZERO);
+    }
+
+    #[test]
+    fn jump_from_completed_starts_a_new_run() {
+        let mut preset = preset();
+        preset.repeat = PresetRepeat::Times(2);
+        let (mut engine, clock) = engine(preset);
+        engine.start();
+        for _ in 0..3 {
+            engine.skip();
+        }
+        clock.advance(MIN);
+        let events = engine.skip();
+        assert!(matches!(
+            events.last(),
+            Some(EngineEvent::PresetCompleted(_))
+        ));
+        assert_eq!(engine.cycle(), 1);
+
+        engine.jump_to(1);
+        assert_eq!((engine.cycle(), engine.session_index()), (0, 1));
+        clock.advance(5 * MIN);
+        engine.tick();
+        clock.advance(25 * MIN);
+        engine.tick();
+        clock.advance(5 * MIN);
+        let events = engine.tick();
+        let Some(EngineEvent::PresetCompleted(summary)) = events.last() else {
+            panic!("expected the preset to complete, got {events:?}");
+        };
+        assert_eq!(summary.completed, 3);
+        assert_eq!(summary.skipped, 0);
+        assert_eq!(summary.break_time, 10 * MIN);
+    }
+
+    #[test]
+    fn reloading_the_preset_keeps_a_stopped_run_in_place() {
+        let mut preset = preset();
+        preset.id = Some(1);
+        preset.repeat = PresetRepeat::Times(2);
+        let (mut engine, clock) = engine(preset.clone());
+        engine.start();
+        engine.skip();
+        engine.skip();
+        clock.advance(MIN);
+        engine.stop();
+        assert!(engine.is_idle());
+        assert_eq!((engine.cycle(), engine.session_index()), (1, 0));
+
+        let mut renamed = preset.clone();
+        renamed.title = "Renamed".into();
+        assert!(!engine.refresh_preset(renamed));
+        assert_eq!((engine.cycle(), engine.session_index()), (1, 0));
+        assert_eq!(engine.preset().title.as_ref(), "Renamed");
+
+        let mut edited = preset;
+        edited.sessions[1].duration = 10 * MIN;
+        assert!(engine.refresh_preset(edited));
+        assert_eq!((engine.cycle(), engine.session_index()), (0, 0));
+    }
+}
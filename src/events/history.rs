@@ -0,0 +1,3 @@
+/// A session was recorded or an entry corrected, totals from the history may be stale
+#[derive(Debug, Clone, Copy)]
+pub struct HistoryChangedEvent;
@@ -1 +1,4 @@
+pub mod history;
 pub mod navigation;
+pub mod preset;
+pub mod summary;
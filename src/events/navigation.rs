@@ -2,7 +2,10 @@
 pub enum Screen {
     Timer,
     Settings,
-    // PresetEdit,
+    PresetEdit,
+    Summary,
+    Statistics,
+    History,
 }
 
 #[derive(Debug, Clone, Copy)]
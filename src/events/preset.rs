@@ -0,0 +1,18 @@
+#[derive(Debug, Clone, Copy)]
+pub struct EditPresetEvent {
+    pub preset_id: Option<i64>, // None when creating a new preset
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PresetSavedEvent;
+
+/// Run this preset on the timer
+#[derive(Debug, Clone, Copy)]
+pub struct UsePresetEvent {
+    pub preset_id: i64,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DeletePresetEvent {
+    pub preset_id: i64,
+}
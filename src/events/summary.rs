@@ -0,0 +1,17 @@
+use crate::engine::summary::RunSummary;
+use crate::session::TimerPreset;
+
+/// The last session of a preset ended, show how the run went
+#[derive(Debug, Clone)]
+pub struct PresetFinishedEvent {
+    pub preset: TimerPreset,
+    pub summary: RunSummary,
+}
+
+/// What to do once a run summary has been seen
+#[derive(Debug, Clone, Copy)]
+pub enum SummaryActionEvent {
+    RepeatPreset,
+    StartPreset(i64),
+    FinishForToday,
+}
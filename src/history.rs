@@ -0,0 +1,134 @@
+use std::time::{Duration, SystemTime};
+
+use gpui::SharedString;
+use sqlx::types::chrono::{DateTime, Utc};
+
+use crate::engine::timer::{PauseInterval, SessionRun};
+use crate::session::SessionKind;
+
+/// How a session run came to an end
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionOutcome {
+    Completed,
+    Stopped,
+    Skipped,
+    Abandoned, // paused for too long, see `PauseLimits::abandon_after`
+}
+
+impl SessionOutcome {
+    /// The value stored in the `outcome` column of the `history` table
+    pub fn as_str(&self) -> &'static str {
+        return match self {
+            SessionOutcome::Completed => "completed",
+            SessionOutcome::Stopped => "stopped",
+            SessionOutcome::Skipped => "skipped",
+            SessionOutcome::Abandoned => "abandoned",
+        };
+    }
+
+    pub fn from_str(value: &str) -> Option<Self> {
+        return match value {
+            "completed" => Some(SessionOutcome::Completed),
+            "stopped" => Some(SessionOutcome::Stopped),
+            "skipped" => Some(SessionOutcome::Skipped),
+            "abandoned" => Some(SessionOutcome::Abandoned),
+            _ => None,
+        };
+    }
+}
+
+/// Where an interruption came from, as the Pomodoro technique tells them apart
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InterruptionKind {
+    Internal, // a thought or urge of your own
+    External, // someone or something else
+}
+
+impl InterruptionKind {
+    /// The value stored in the `kind` column of the `history_interruption` table
+    pub fn as_str(&self) -> &'static str {
+        return match self {
+            InterruptionKind::Internal => "internal",
+            InterruptionKind::External => "external",
+        };
+    }
+
+    pub fn from_str(value: &str) -> Option<Self> {
+        return match value {
+            "internal" => Some(InterruptionKind::Internal),
+            "external" => Some(InterruptionKind::External),
+            _ => None,
+        };
+    }
+}
+
+/// An interruption logged while a session kept running
+#[derive(Debug, Clone)]
+pub struct Interruption {
+    pub kind: InterruptionKind,
+    pub logged_at: SystemTime,
+    pub note: Option<SharedString>,
+}
+
+/// One focus or break session that was actually run
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    pub id: Option<i64>,
+    pub preset_id: Option<i64>,
+    pub session_title: SharedString,
+    pub kind: SessionKind,
+    pub planned: Duration,
+    pub extended: Duration, // time added to the session while it ran
+    pub elapsed: Duration,
+    pub overtime: Duration, // counted past the end before the session was acknowledged
+    pub paused: Duration,
+    pub pauses: Vec<PauseInterval>,
+    pub interruptions: Vec<Interruption>,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    pub outcome: SessionOutcome,
+    pub adhoc: bool, // a quick timer or stopwatch run
+}
+
+impl HistoryEntry {
+    /// The entry for a session the engine ran, under the session's own title
+    pub fn from_run(
+        run: &SessionRun,
+        preset_id: Option<i64>,
+        started_at: DateTime<Utc>,
+        ended_at: DateTime<Utc>,
+    ) -> Self {
+        return Self {
+            id: None,
+            preset_id,
+            session_title: run.session.title.clone(),
+            kind: run.session.kind,
+            planned: run.planned,
+            extended: run.extended,
+            elapsed: run.elapsed,
+            overtime: run.overtime,
+            paused: run.paused(),
+            pauses: run.pauses.clone(),
+            interruptions: run.interruptions.clone(),
+            started_at,
+            ended_at,
+            outcome: run.outcome,
+            adhoc: false,
+        };
+    }
+
+    /// Whether the entry counts toward the focus statistics, goals and achievements
+    pub fn is_focus(&self) -> bool {
+        return self.kind == SessionKind::WORK && !self.adhoc;
+    }
+}
+
+/// What the history browser narrows the list down to, None matches anything
+#[derive(Debug, Clone, Default)]
+pub struct HistoryFilter {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub preset_id: Option<i64>,
+    pub kind: Option<SessionKind>,
+    pub outcome: Option<SessionOutcome>,
+}
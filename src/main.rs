@@ -1,14 +1,21 @@
-use crate::{app::BmoApp, assets::Assets};
+use crate::{app::BmoApp, assets::Assets, db::Database};
 use gpui::*;
-use gpui_component::{Root, Theme, TitleBar};
+use gpui_component::{Root, TitleBar};
 
+mod achievements;
 mod app;
 mod assets;
 mod components;
 mod constants;
 mod db;
+mod duration;
+mod engine;
 mod events;
+mod history;
+mod repository;
 mod session;
+mod settings;
+mod statistics;
 mod theme;
 
 fn window_options(cx: &App) -> WindowOptions {
@@ -25,6 +32,23 @@ fn window_options(cx: &App) -> WindowOptions {
     };
 }
 
+/// Open the database, load the settings and show the app
+async fn open_main_window(options: WindowOptions, cx: &mut AsyncApp) -> anyhow::Result<()> {
+    let database = Database::connect(Database::default_path()).await?;
+    let settings = database.settings().load().await?;
+    cx.update(|cx| {
+        cx.set_global(database);
+        cx.set_global(settings);
+    })?;
+
+    cx.open_window(options, |window, cx| {
+        let view = cx.new(|cx| BmoApp::new(cx, window));
+        cx.new(|cx| Root::new(view, window, cx))
+    })?;
+
+    return Ok(());
+}
+
 fn main() {
     let appl = Application::new().with_assets(Assets);
 
@@ -34,20 +58,19 @@ fn main() {
         theme::init(cx);
         // close app when all windows are closed
         cx.on_window_closed(|cx| {
-            if cx.windows().len() == 0 {
+            if cx.windows().is_empty() {
                 cx.quit();
             }
         })
         .detach();
 
         let w_options = window_options(cx);
-        cx.spawn(async move |cx| -> anyhow::Result<()> {
-            cx.open_window(w_options, |window, cx| {
-                let view = cx.new(|cx| BmoApp::new(cx, window));
-                cx.new(|cx| Root::new(view, window, cx))
-            })?;
-
-            return Ok(());
+        cx.spawn(async move |cx| {
+            // without the database there's nothing to show, say why rather than hang around
+            if let Err(err) = open_main_window(w_options, cx).await {
+                eprintln!("bmo could not start: {err:#}");
+                std::process::exit(1);
+            }
         })
         .detach();
     });
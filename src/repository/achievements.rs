@@ -0,0 +1,47 @@
+use sqlx::SqlitePool;
+use sqlx::types::chrono::{DateTime, Utc};
+
+use crate::achievements::Achievement;
+
+/// Keeps the unlocked achievements in the `achievement` table
+#[derive(Clone)]
+pub struct AchievementRepository {
+    pool: SqlitePool,
+}
+
+impl AchievementRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        return Self { pool };
+    }
+
+    /// Record an achievement as unlocked, false if it already was
+    pub async fn unlock(
+        &self,
+        achievement: Achievement,
+        at: DateTime<Utc>,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "INSERT INTO achievement (id, unlocked_at) VALUES (?, ?)
+             ON CONFLICT(id) DO NOTHING",
+        )
+        .bind(achievement.as_str())
+        .bind(at)
+        .execute(&self.pool)
+        .await?;
+
+        return Ok(result.rows_affected() > 0);
+    }
+
+    /// Every unlocked achievement with when it was unlocked, ids no longer known are left out
+    pub async fn list(&self) -> Result<Vec<(Achievement, DateTime<Utc>)>, sqlx::Error> {
+        let rows: Vec<(String, DateTime<Utc>)> =
+            sqlx::query_as("SELECT id, unlocked_at FROM achievement ORDER BY unlocked_at")
+                .fetch_all(&self.pool)
+                .await?;
+
+        return Ok(rows
+            .into_iter()
+            .filter_map(|(id, at)| Some((Achievement::from_str(&id)?, at)))
+            .collect());
+    }
+}
@@ -0,0 +1,88 @@
+use sqlx::SqlitePool;
+use sqlx::types::chrono::NaiveDate;
+
+use crate::statistics::DailyGoal;
+
+/// Keeps which daily goal applied from which day, in the `daily_goal` table
+#[derive(Clone)]
+pub struct GoalRepository {
+    pool: SqlitePool,
+}
+
+impl GoalRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        return Self { pool };
+    }
+
+    /// Set the goal from `day` on, replacing one set earlier that same day
+    pub async fn save(&self, day: NaiveDate, goal: DailyGoal) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO daily_goal (day, goal) VALUES (?, ?)
+             ON CONFLICT(day) DO UPDATE SET goal = excluded.goal",
+        )
+        .bind(day)
+        .bind(goal.to_text())
+        .execute(&self.pool)
+        .await?;
+
+        return Ok(());
+    }
+
+    /// Every goal change, oldest first, unreadable goals count as no goal
+    pub async fn list(&self) -> Result<Vec<(NaiveDate, DailyGoal)>, sqlx::Error> {
+        let rows: Vec<(NaiveDate, String)> =
+            sqlx::query_as("SELECT day, goal FROM daily_goal ORDER BY day")
+                .fetch_all(&self.pool)
+                .await?;
+
+        return Ok(rows
+            .into_iter()
+            .map(|(day, goal)| (day, DailyGoal::parse(&goal).unwrap_or_default()))
+            .collect());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+
+    fn date(day: u32) -> NaiveDate {
+        return NaiveDate::from_ymd_opt(2026, 3, day).unwrap();
+    }
+
+    #[test]
+    fn goals_are_listed_oldest_first_one_per_day() {
+        async_std::task::block_on(async {
+            let pool = Database::create_memory_pool().await.unwrap();
+            let repository = GoalRepository::new(pool.clone());
+
+            repository
+                .save(date(10), DailyGoal::Sessions(4))
+                .await
+                .unwrap();
+            repository
+                .save(date(3), DailyGoal::Minutes(60))
+                .await
+                .unwrap();
+            repository
+                .save(date(10), DailyGoal::Minutes(90))
+                .await
+                .unwrap();
+            sqlx::query("INSERT INTO daily_goal (day, goal) VALUES (?, 'hours:2')")
+                .bind(date(20))
+                .execute(&pool)
+                .await
+                .unwrap();
+
+            assert_eq!(
+                repository.list().await.unwrap(),
+                [
+                    (date(3), DailyGoal::Minutes(60)),
+                    (date(10), DailyGoal::Minutes(90)),
+                    (date(20), DailyGoal::Off),
+                ]
+            );
+        });
+    }
+}
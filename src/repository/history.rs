@@ -0,0 +1,561 @@
+use std::time::{Duration, SystemTime};
+
+use sqlx::SqlitePool;
+use sqlx::types::chrono::{DateTime, NaiveDate, Utc};
+
+use crate::engine::timer::PauseInterval;
+use crate::history::{HistoryEntry, HistoryFilter, Interruption, InterruptionKind, SessionOutcome};
+use crate::session::SessionKind;
+use crate::statistics::DayFocus;
+
+#[derive(sqlx::FromRow)]
+struct HistoryRow {
+    id: i64,
+    preset_id: Option<i64>,
+    session_title: String,
+    #[sqlx(rename = "type")]
+    kind: String,
+    planned_duration_in_sec: i64,
+    extended_in_sec: i64,
+    elapsed_in_sec: i64,
+    overtime_in_sec: i64,
+    paused_in_sec: i64,
+    started_at: DateTime<Utc>,
+    ended_at: DateTime<Utc>,
+    outcome: String,
+    adhoc: bool,
+}
+
+#[derive(sqlx::FromRow)]
+struct PauseRow {
+    history_id: i64,
+    started_at: DateTime<Utc>,
+    length_in_sec: i64,
+}
+
+impl PauseRow {
+    fn into_interval(self) -> PauseInterval {
+        return PauseInterval {
+            started_at: SystemTime::from(self.started_at),
+            length: Duration::from_secs(self.length_in_sec.max(0) as u64),
+        };
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct InterruptionRow {
+    history_id: i64,
+    kind: String,
+    logged_at: DateTime<Utc>,
+    note: Option<String>,
+}
+
+impl InterruptionRow {
+    fn into_interruption(self) -> Result<Interruption, sqlx::Error> {
+        let kind = InterruptionKind::from_str(&self.kind).ok_or_else(|| {
+            sqlx::Error::Decode(format!("unknown interruption kind \"{}\"", self.kind).into())
+        })?;
+
+        return Ok(Interruption {
+            kind,
+            logged_at: SystemTime::from(self.logged_at),
+            note: self.note.map(|note| note.into()),
+        });
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct DayFocusRow {
+    day: NaiveDate,
+    focus_in_sec: i64,
+    sessions: i64,
+    completed: i64,
+}
+
+impl HistoryRow {
+    fn into_entry(self) -> Result<HistoryEntry, sqlx::Error> {
+        let kind = SessionKind::from_str(&self.kind).ok_or_else(|| {
+            sqlx::Error::Decode(format!("unknown session type \"{}\"", self.kind).into())
+        })?;
+        let outcome = SessionOutcome::from_str(&self.outcome).ok_or_else(|| {
+            sqlx::Error::Decode(format!("unknown session outcome \"{}\"", self.outcome).into())
+        })?;
+
+        return Ok(HistoryEntry {
+            id: Some(self.id),
+            preset_id: self.preset_id,
+            session_title: self.session_title.into(),
+            kind,
+            planned: Duration::from_secs(self.planned_duration_in_sec.max(0) as u64),
+            extended: Duration::from_secs(self.extended_in_sec.max(0) as u64),
+            elapsed: Duration::from_secs(self.elapsed_in_sec.max(0) as u64),
+            overtime: Duration::from_secs(self.overtime_in_sec.max(0) as u64),
+            paused: Duration::from_secs(self.paused_in_sec.max(0) as u64),
+            pauses: vec![],
+            interruptions: vec![],
+            started_at: self.started_at,
+            ended_at: self.ended_at,
+            outcome,
+            adhoc: self.adhoc,
+        });
+    }
+}
+
+/// Writes every session that was run to the `history` table
+#[derive(Clone)]
+pub struct HistoryRepository {
+    pool: SqlitePool,
+}
+
+impl HistoryRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        return Self { pool };
+    }
+
+    /// Record a finished session run with its pauses and interruptions, returning the new entry id
+    pub async fn record(&self, entry: &HistoryEntry) -> Result<i64, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let id = sqlx::query(
+            "INSERT INTO history (
+                preset_id, session_title, type, planned_duration_in_sec, extended_in_sec,
+                elapsed_in_sec, overtime_in_sec, paused_in_sec, started_at, ended_at, outcome,
+                adhoc
+             ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(entry.preset_id)
+        .bind(entry.session_title.as_ref())
+        .bind(entry.kind.as_str())
+        .bind(entry.planned.as_secs() as i64)
+        .bind(entry.extended.as_secs() as i64)
+        .bind(entry.elapsed.as_secs() as i64)
+        .bind(entry.overtime.as_secs() as i64)
+        .bind(entry.paused.as_secs() as i64)
+        .bind(entry.started_at)
+        .bind(entry.ended_at)
+        .bind(entry.outcome.as_str())
+        .bind(entry.adhoc)
+        .execute(&mut *tx)
+        .await?
+        .last_insert_rowid();
+
+        for pause in &entry.pauses {
+            sqlx::query(
+                "INSERT INTO history_pause (history_id, started_at, length_in_sec)
+                 VALUES (?, ?, ?)",
+            )
+            .bind(id)
+            .bind(DateTime::<Utc>::from(pause.started_at))
+            .bind(pause.length.as_secs() as i64)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for interruption in &entry.interruptions {
+            sqlx::query(
+                "INSERT INTO history_interruption (history_id, kind, logged_at, note)
+                 VALUES (?, ?, ?, ?)",
+            )
+            .bind(id)
+            .bind(interruption.kind.as_str())
+            .bind(DateTime::<Utc>::from(interruption.logged_at))
+            .bind(interruption.note.as_ref().map(|note| note.to_string()))
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        return Ok(id);
+    }
+
+    /// Entries matching `filter`, newest first and at most `limit` of them.
+    /// Pauses and interruptions aren't loaded, only the paused total
+    pub async fn list(
+        &self,
+        filter: &HistoryFilter,
+        limit: u32,
+    ) -> Result<Vec<HistoryEntry>, sqlx::Error> {
+        let rows: Vec<HistoryRow> = sqlx::query_as(
+            "SELECT id, preset_id, session_title, type, planned_duration_in_sec, extended_in_sec,
+                    elapsed_in_sec, overtime_in_sec, paused_in_sec, started_at, ended_at, outcome,
+                    adhoc
+             FROM history
+             WHERE (?1 IS NULL OR started_at >= ?1)
+               AND (?2 IS NULL OR started_at < ?2)
+               AND (?3 IS NULL OR preset_id = ?3)
+               AND (?4 IS NULL OR type = ?4)
+               AND (?5 IS NULL OR outcome = ?5)
+             ORDER BY started_at DESC
+             LIMIT ?6",
+        )
+        .bind(filter.from)
+        .bind(filter.to)
+        .bind(filter.preset_id)
+        .bind(filter.kind.map(|kind| kind.as_str()))
+        .bind(filter.outcome.map(|outcome| outcome.as_str()))
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+
+        return rows.into_iter().map(HistoryRow::into_entry).collect();
+    }
+
+    /// Correct a recorded entry, its pauses and interruptions are left as they are
+    pub async fn update(&self, entry: &HistoryEntry) -> Result<(), sqlx::Error> {
+        let Some(id) = entry.id else {
+            return Err(sqlx::Error::RowNotFound);
+        };
+
+        let updated = sqlx::query(
+            "UPDATE history SET
+                preset_id = ?, session_title = ?, type = ?, planned_duration_in_sec = ?,
+                elapsed_in_sec = ?, started_at = ?, ended_at = ?, outcome = ?
+             WHERE id = ?",
+        )
+        .bind(entry.preset_id)
+        .bind(entry.session_title.as_ref())
+        .bind(entry.kind.as_str())
+        .bind(entry.planned.as_secs() as i64)
+        .bind(entry.elapsed.as_secs() as i64)
+        .bind(entry.started_at)
+        .bind(entry.ended_at)
+        .bind(entry.outcome.as_str())
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        if updated.rows_affected() == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+        return Ok(());
+    }
+
+    /// Remove an entry along with its pauses and interruptions
+    pub async fn delete(&self, id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM history WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+        return Ok(());
+    }
+
+    /// Entries that started within `[from, to)`, oldest first
+    pub async fn list_between(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<HistoryEntry>, sqlx::Error> {
+        let rows: Vec<HistoryRow> = sqlx::query_as(
+            "SELECT id, preset_id, session_title, type, planned_duration_in_sec, extended_in_sec,
+                    elapsed_in_sec, overtime_in_sec, paused_in_sec, started_at, ended_at, outcome,
+                    adhoc
+             FROM history
+             WHERE started_at >= ? AND started_at < ?
+             ORDER BY started_at",
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let pauses: Vec<PauseRow> = sqlx::query_as(
+            "SELECT p.history_id, p.started_at, p.length_in_sec
+             FROM history_pause p
+             JOIN history h ON h.id = p.history_id
+             WHERE h.started_at >= ? AND h.started_at < ?
+             ORDER BY p.started_at",
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let interruptions: Vec<InterruptionRow> = sqlx::query_as(
+            "SELECT i.history_id, i.kind, i.logged_at, i.note
+             FROM history_interruption i
+             JOIN history h ON h.id = i.history_id
+             WHERE h.started_at >= ? AND h.started_at < ?
+             ORDER BY i.logged_at",
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut entries = rows
+            .into_iter()
+            .map(HistoryRow::into_entry)
+            .collect::<Result<Vec<_>, _>>()?;
+        for row in pauses {
+            if let Some(entry) = entries.iter_mut().find(|e| e.id == Some(row.history_id)) {
+                entry.pauses.push(row.into_interval());
+            }
+        }
+        for row in interruptions {
+            if let Some(entry) = entries.iter_mut().find(|e| e.id == Some(row.history_id)) {
+                entry.interruptions.push(row.into_interruption()?);
+            }
+        }
+
+        return Ok(entries);
+    }
+
+    /// Focus time and focus sessions per local day for entries that started within `[from, to)`,
+    /// days without any focus are left out
+    pub async fn focus_per_day(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<DayFocus>, sqlx::Error> {
+        let rows: Vec<DayFocusRow> = sqlx::query_as(
+            "SELECT date(started_at, 'localtime') AS day,
+                    SUM(elapsed_in_sec) AS focus_in_sec,
+                    COUNT(*) AS sessions,
+                    SUM(outcome = 'completed') AS completed
+             FROM history
+             WHERE type = 'focus' AND NOT adhoc AND started_at >= ? AND started_at < ?
+             GROUP BY day
+             ORDER BY day",
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+
+        return Ok(rows
+            .into_iter()
+            .map(|row| DayFocus {
+                day: row.day,
+                focus: Duration::from_secs(row.focus_in_sec.max(0) as u64),
+                sessions: row.sessions.max(0) as u32,
+                completed: row.completed.max(0) as u32,
+            })
+            .collect());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+
+    async fn repository() -> HistoryRepository {
+        let pool = Database::create_memory_pool().await.unwrap();
+        return HistoryRepository::new(pool);
+    }
+
+    fn focus_entry(started_at: DateTime<Utc>, adhoc: bool) -> HistoryEntry {
+        let elapsed = Duration::from_secs(1500);
+        return HistoryEntry {
+            id: None,
+            preset_id: None,
+            session_title: "Focus".into(),
+            kind: SessionKind::WORK,
+            planned: elapsed,
+            extended: Duration::ZERO,
+            elapsed,
+            overtime: Duration::ZERO,
+            paused: Duration::ZERO,
+            pauses: vec![],
+            interruptions: vec![],
+            started_at,
+            ended_at: started_at,
+            outcome: SessionOutcome::Completed,
+            adhoc,
+        };
+    }
+
+    #[test]
+    fn adhoc_runs_are_kept_but_not_counted_as_focus() {
+        async_std::task::block_on(async {
+            let repository = repository().await;
+            let now = Utc::now();
+            repository.record(&focus_entry(now, false)).await.unwrap();
+            repository.record(&focus_entry(now, true)).await.unwrap();
+
+            let from = now - chrono::Duration::days(1);
+            let to = now + chrono::Duration::days(1);
+            let entries = repository.list_between(from, to).await.unwrap();
+            assert_eq!(entries.len(), 2);
+            assert_eq!(entries.iter().filter(|entry| entry.adhoc).count(), 1);
+
+            let days = repository.focus_per_day(from, to).await.unwrap();
+            assert_eq!(days.iter().map(|day| day.sessions).sum::<u32>(), 1);
+            assert_eq!(days.iter().map(|day| day.completed).sum::<u32>(), 1);
+        });
+    }
+
+    #[test]
+    fn update_corrects_the_entry_but_keeps_its_pauses() {
+        async_std::task::block_on(async {
+            let repository = repository().await;
+            let now = Utc::now();
+            let mut entry = focus_entry(now, false);
+            entry.pauses = vec![PauseInterval {
+                started_at: now.into(),
+                length: Duration::from_secs(120),
+            }];
+            let id = repository.record(&entry).await.unwrap();
+
+            let corrected = HistoryEntry {
+                id: Some(id),
+                session_title: "Reading".into(),
+                kind: SessionKind::BREAK,
+                elapsed: Duration::from_secs(600),
+                outcome: SessionOutcome::Stopped,
+                pauses: vec![],
+                ..entry.clone()
+            };
+            repository.update(&corrected).await.unwrap();
+
+            let from = now - chrono::Duration::hours(1);
+            let to = now + chrono::Duration::hours(1);
+            let loaded = &repository.list_between(from, to).await.unwrap()[0];
+            assert_eq!(loaded.session_title.as_ref(), "Reading");
+            assert_eq!(loaded.kind, SessionKind::BREAK);
+            assert_eq!(loaded.elapsed, Duration::from_secs(600));
+            assert_eq!(loaded.outcome, SessionOutcome::Stopped);
+            assert_eq!(loaded.pauses.len(), 1);
+
+            let missing = HistoryEntry {
+                id: Some(id + 1),
+                ..corrected
+            };
+            assert!(repository.update(&missing).await.is_err());
+            assert!(repository.update(&entry).await.is_err());
+        });
+    }
+
+    #[test]
+    fn delete_removes_the_entry_with_its_pauses_and_interruptions() {
+        async_std::task::block_on(async {
+            let repository = repository().await;
+            let now = Utc::now();
+            let mut entry = focus_entry(now, false);
+            entry.pauses = vec![PauseInterval {
+                started_at: now.into(),
+                length: Duration::from_secs(120),
+            }];
+            entry.interruptions = vec![Interruption {
+                kind: InterruptionKind::External,
+                logged_at: now.into(),
+                note: Some("Phone".into()),
+            }];
+            let deleted = repository.record(&entry).await.unwrap();
+            let kept = repository.record(&focus_entry(now, false)).await.unwrap();
+
+            repository.delete(deleted).await.unwrap();
+
+            let entries = repository
+                .list(&HistoryFilter::default(), 10)
+                .await
+                .unwrap();
+            let ids: Vec<_> = entries.iter().map(|entry| entry.id).collect();
+            assert_eq!(ids, [Some(kept)]);
+
+            let (pauses,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM history_pause")
+                .fetch_one(&repository.pool)
+                .await
+                .unwrap();
+            let (interruptions,): (i64,) =
+                sqlx::query_as("SELECT COUNT(*) FROM history_interruption")
+                    .fetch_one(&repository.pool)
+                    .await
+                    .unwrap();
+            assert_eq!((pauses, interruptions), (0, 0));
+        });
+    }
+
+    #[test]
+    fn list_narrows_down_by_every_filter_field() {
+        async_std::task::block_on(async {
+            let repository = repository().await;
+            let now = Utc::now();
+            let hour = chrono::Duration::hours(1);
+
+            let early = focus_entry(now - hour * 3, false);
+            let other_preset = HistoryEntry {
+                preset_id: Some(7),
+                ..focus_entry(now - hour * 2, false)
+            };
+            let rest = HistoryEntry {
+                kind: SessionKind::BREAK,
+                ..focus_entry(now - hour, false)
+            };
+            let skipped = HistoryEntry {
+                outcome: SessionOutcome::Skipped,
+                ..focus_entry(now, false)
+            };
+            for entry in [&early, &other_preset, &rest, &skipped] {
+                repository.record(entry).await.unwrap();
+            }
+
+            let starts = |filter: HistoryFilter| {
+                let repository = repository.clone();
+                async move {
+                    return repository
+                        .list(&filter, 10)
+                        .await
+                        .unwrap()
+                        .iter()
+                        .map(|entry| entry.started_at.timestamp())
+                        .collect::<Vec<_>>();
+                }
+            };
+
+            // newest first, and the limit applies after filtering
+            let all = starts(HistoryFilter::default()).await;
+            assert_eq!(all.len(), 4);
+            assert!(all.windows(2).all(|pair| pair[0] > pair[1]));
+            assert_eq!(
+                repository
+                    .list(&HistoryFilter::default(), 2)
+                    .await
+                    .unwrap()
+                    .len(),
+                2
+            );
+
+            let from = HistoryFilter {
+                from: Some(now - hour),
+                ..Default::default()
+            };
+            assert_eq!(
+                starts(from).await,
+                [skipped.started_at.timestamp(), rest.started_at.timestamp()]
+            );
+
+            // `to` is exclusive
+            let to = HistoryFilter {
+                to: Some(now - hour * 2),
+                ..Default::default()
+            };
+            assert_eq!(starts(to).await, [early.started_at.timestamp()]);
+
+            let preset = HistoryFilter {
+                preset_id: Some(7),
+                ..Default::default()
+            };
+            assert_eq!(starts(preset).await, [other_preset.started_at.timestamp()]);
+
+            let kind = HistoryFilter {
+                kind: Some(SessionKind::BREAK),
+                ..Default::default()
+            };
+            assert_eq!(starts(kind).await, [rest.started_at.timestamp()]);
+
+            let outcome = HistoryFilter {
+                outcome: Some(SessionOutcome::Skipped),
+                ..Default::default()
+            };
+            assert_eq!(starts(outcome).await, [skipped.started_at.timestamp()]);
+
+            let nothing = HistoryFilter {
+                kind: Some(SessionKind::BREAK),
+                outcome: Some(SessionOutcome::Skipped),
+                ..Default::default()
+            };
+            assert!(starts(nothing).await.is_empty());
+        });
+    }
+}
@@ -0,0 +1,6 @@
+pub mod achievements;
+pub mod goals;
+pub mod history;
+pub mod presets;
+pub mod run_state;
+pub mod settings;
@@ -0,0 +1,560 @@
+use std::time::Duration;
+
+use sqlx::{Sqlite, SqlitePool, Transaction};
+
+use crate::session::{
+    AutoStart, FlowBreaks, PauseLimits, PresetRepeat, PresetRule, Session, SessionKind, TimerPreset,
+};
+
+#[derive(sqlx::FromRow)]
+struct PresetRow {
+    id: i64,
+    name: String,
+    description: Option<String>,
+    repeat_count: i64,
+    auto_start_focus: bool,
+    auto_start_breaks: bool,
+    auto_start_delay_in_sec: i64,
+    flow_breaks: String,
+    max_pauses: Option<i64>,
+    max_paused_in_sec: Option<i64>,
+    abandon_after_in_sec: Option<i64>,
+}
+
+#[derive(sqlx::FromRow)]
+struct SessionRow {
+    preset_id: i64,
+    name: String,
+    duration_in_sec: i64,
+    color: i64,
+    #[sqlx(rename = "type")]
+    kind: String,
+    auto_start: Option<bool>,
+    open_ended: bool,
+}
+
+#[derive(sqlx::FromRow)]
+struct PresetRuleRow {
+    preset_id: i64,
+    focus_in_sec: i64,
+    short_break_in_sec: i64,
+    long_break_in_sec: i64,
+    long_break_every: i64,
+    focus_blocks: i64,
+}
+
+impl PresetRuleRow {
+    fn into_rule(self) -> PresetRule {
+        return PresetRule {
+            focus: Duration::from_secs(self.focus_in_sec.max(0) as u64),
+            short_break: Duration::from_secs(self.short_break_in_sec.max(0) as u64),
+            long_break: Duration::from_secs(self.long_break_in_sec.max(0) as u64),
+            long_break_every: self.long_break_every.max(1) as u32,
+            focus_blocks: self.focus_blocks.max(1) as u32,
+        };
+    }
+}
+
+impl SessionRow {
+    fn into_session(self) -> Result<Session, sqlx::Error> {
+        let kind = SessionKind::from_str(&self.kind).ok_or_else(|| {
+            sqlx::Error::Decode(format!("unknown session type \"{}\"", self.kind).into())
+        })?;
+
+        return Ok(Session {
+            title: self.name.into(),
+            duration: Duration::from_secs(self.duration_in_sec.max(0) as u64),
+            kind,
+            color: self.color as u32,
+            auto_start: self.auto_start,
+            open_ended: self.open_ended,
+        });
+    }
+}
+
+/// Reads and writes `TimerPreset`s (with their sessions and rule)
+/// from the `presets`, `session` and `preset_rule` tables
+#[derive(Clone)]
+pub struct PresetRepository {
+    pool: SqlitePool,
+}
+
+impl PresetRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        return Self { pool };
+    }
+
+    /// All presets that haven't been deleted, oldest first
+    pub async fn list(&self) -> Result<Vec<TimerPreset>, sqlx::Error> {
+        let presets: Vec<PresetRow> = sqlx::query_as(
+            "SELECT id, name, description, repeat_count,
+                    auto_start_focus, auto_start_breaks, auto_start_delay_in_sec, flow_breaks,
+                    max_pauses, max_paused_in_sec, abandon_after_in_sec
+             FROM presets WHERE is_deleted = 0 ORDER BY id",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let sessions: Vec<SessionRow> = sqlx::query_as(
+            "SELECT s.preset_id, s.name, s.duration_in_sec, s.color, s.type, s.auto_start,
+                    s.open_ended
+             FROM session s
+             JOIN presets p ON p.id = s.preset_id
+             WHERE p.is_deleted = 0
+             ORDER BY s.preset_id, s.position",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let rules: Vec<PresetRuleRow> = sqlx::query_as(
+            "SELECT r.preset_id, r.focus_in_sec, r.short_break_in_sec, r.long_break_in_sec,
+                    r.long_break_every, r.focus_blocks
+             FROM preset_rule r
+             JOIN presets p ON p.id = r.preset_id
+             WHERE p.is_deleted = 0",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut result: Vec<TimerPreset> = presets.into_iter().map(preset_from_row).collect();
+        for row in sessions {
+            if let Some(preset) = result.iter_mut().find(|p| p.id == Some(row.preset_id)) {
+                preset.sessions.push(row.into_session()?);
+            }
+        }
+        for row in rules {
+            if let Some(preset) = result.iter_mut().find(|p| p.id == Some(row.preset_id)) {
+                preset.rule = Some(row.into_rule());
+            }
+        }
+
+        return Ok(result);
+    }
+
+    pub async fn get(&self, id: i64) -> Result<Option<TimerPreset>, sqlx::Error> {
+        let row: Option<PresetRow> = sqlx::query_as(
+            "SELECT id, name, description, repeat_count,
+                    auto_start_focus, auto_start_breaks, auto_start_delay_in_sec, flow_breaks,
+                    max_pauses, max_paused_in_sec, abandon_after_in_sec
+             FROM presets WHERE id = ? AND is_deleted = 0",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let sessions: Vec<SessionRow> = sqlx::query_as(
+            "SELECT preset_id, name, duration_in_sec, color, type, auto_start, open_ended
+             FROM session WHERE preset_id = ? ORDER BY position",
+        )
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let rule: Option<PresetRuleRow> = sqlx::query_as(
+            "SELECT preset_id, focus_in_sec, short_break_in_sec, long_break_in_sec,
+                    long_break_every, focus_blocks
+             FROM preset_rule WHERE preset_id = ?",
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        let mut preset = preset_from_row(row);
+        for row in sessions {
+            preset.sessions.push(row.into_session()?);
+        }
+        preset.rule = rule.map(|row| row.into_rule());
+
+        return Ok(Some(preset));
+    }
+
+    /// Insert a new preset with its sessions, returning the new preset id
+    pub async fn create(&self, preset: &TimerPreset) -> Result<i64, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let preset_id = sqlx::query(
+            "INSERT INTO presets (
+                name, description, repeat_count,
+                auto_start_focus, auto_start_breaks, auto_start_delay_in_sec, flow_breaks,
+                max_pauses, max_paused_in_sec, abandon_after_in_sec
+             ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(preset.title.as_ref())
+        .bind(preset.description.as_ref().map(|d| d.to_string()))
+        .bind(preset.repeat.as_count())
+        .bind(preset.auto_start.focus)
+        .bind(preset.auto_start.breaks)
+        .bind(preset.auto_start.delay.as_secs() as i64)
+        .bind(preset.flow_breaks.to_text())
+        .bind(preset.pause_limits.max_pauses.map(i64::from))
+        .bind(preset.pause_limits.max_paused.map(|d| d.as_secs() as i64))
+        .bind(
+            preset
+                .pause_limits
+                .abandon_after
+                .map(|d| d.as_secs() as i64),
+        )
+        .execute(&mut *tx)
+        .await?
+        .last_insert_rowid();
+
+        insert_sessions(&mut tx, preset_id, &preset.sessions).await?;
+        save_rule(&mut tx, preset_id, preset.rule.as_ref()).await?;
+        tx.commit().await?;
+
+        return Ok(preset_id);
+    }
+
+    /// Save the preset with its settings, sessions and rule.
+    /// The session list is replaced as a whole, so removed sessions are dropped
+    pub async fn update(&self, preset: &TimerPreset) -> Result<(), sqlx::Error> {
+        let Some(preset_id) = preset.id else {
+            return Err(sqlx::Error::RowNotFound);
+        };
+
+        let mut tx = self.pool.begin().await?;
+
+        let updated = sqlx::query(
+            "UPDATE presets SET
+                name = ?, description = ?, repeat_count = ?,
+                auto_start_focus = ?, auto_start_breaks = ?, auto_start_delay_in_sec = ?,
+                flow_breaks = ?, max_pauses = ?, max_paused_in_sec = ?, abandon_after_in_sec = ?
+             WHERE id = ? AND is_deleted = 0",
+        )
+        .bind(preset.title.as_ref())
+        .bind(preset.description.as_ref().map(|d| d.to_string()))
+        .bind(preset.repeat.as_count())
+        .bind(preset.auto_start.focus)
+        .bind(preset.auto_start.breaks)
+        .bind(preset.auto_start.delay.as_secs() as i64)
+        .bind(preset.flow_breaks.to_text())
+        .bind(preset.pause_limits.max_pauses.map(i64::from))
+        .bind(preset.pause_limits.max_paused.map(|d| d.as_secs() as i64))
+        .bind(
+            preset
+                .pause_limits
+                .abandon_after
+                .map(|d| d.as_secs() as i64),
+        )
+        .bind(preset_id)
+        .execute(&mut *tx)
+        .await?;
+
+        if updated.rows_affected() == 0 {
+            return Err(sqlx::Error::RowNotFound);
+        }
+
+        sqlx::query("DELETE FROM session WHERE preset_id = ?")
+            .bind(preset_id)
+            .execute(&mut *tx)
+            .await?;
+
+        insert_sessions(&mut tx, preset_id, &preset.sessions).await?;
+        save_rule(&mut tx, preset_id, preset.rule.as_ref()).await?;
+        tx.commit().await?;
+
+        return Ok(());
+    }
+
+    /// Soft delete, the row is kept around so history can still refer to it
+    pub async fn delete(&self, id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE presets SET is_deleted = 1 WHERE id = ?")
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        return Ok(());
+    }
+
+    /// Every preset's title by id, deleted ones too since history still refers to them
+    pub async fn titles(&self) -> Result<Vec<(i64, String)>, sqlx::Error> {
+        let titles = sqlx::query_as("SELECT id, name FROM presets ORDER BY id")
+            .fetch_all(&self.pool)
+            .await?;
+
+        return Ok(titles);
+    }
+
+    /// Load the saved presets, creating the default one on first launch
+    pub async fn list_or_seed(&self) -> Result<Vec<TimerPreset>, sqlx::Error> {
+        let presets = self.list().await?;
+        if !presets.is_empty() {
+            return Ok(presets);
+        }
+
+        self.create(&TimerPreset::default()).await?;
+        return self.list().await;
+    }
+}
+
+fn preset_from_row(row: PresetRow) -> TimerPreset {
+    return TimerPreset {
+        id: Some(row.id),
+        title: row.name.into(),
+        description: row.description.map(|d| d.into()),
+        sessions: vec![],
+        repeat: PresetRepeat::from_count(row.repeat_count),
+        rule: None,
+        auto_start: AutoStart {
+            focus: row.auto_start_focus,
+            breaks: row.auto_start_breaks,
+            delay: Duration::from_secs(row.auto_start_delay_in_sec.max(0) as u64),
+        },
+        flow_breaks: FlowBreaks::parse(&row.flow_breaks).unwrap_or_default(),
+        pause_limits: PauseLimits {
+            max_pauses: row
+                .max_pauses
+                .map(|count| count.clamp(0, u32::MAX as i64) as u32),
+            max_paused: row
+                .max_paused_in_sec
+                .map(|secs| Duration::from_secs(secs.max(0) as u64)),
+            abandon_after: row
+                .abandon_after_in_sec
+                .map(|secs| Duration::from_secs(secs.max(0) as u64)),
+        },
+    };
+}
+
+async fn insert_session(
+    tx: &mut Transaction<'_, Sqlite>,
+    preset_id: i64,
+    position: i64,
+    session: &Session,
+) -> Result<i64, sqlx::Error> {
+    let id = sqlx::query(
+        "INSERT INTO session (
+            preset_id, position, name, duration_in_sec, color, type, auto_start, open_ended
+         ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(preset_id)
+    .bind(position)
+    .bind(session.title.as_ref())
+    .bind(session.duration.as_secs() as i64)
+    .bind(session.color as i64)
+    .bind(session.kind.as_str())
+    .bind(session.auto_start)
+    .bind(session.open_ended)
+    .execute(&mut **tx)
+    .await?
+    .last_insert_rowid();
+
+    return Ok(id);
+}
+
+async fn insert_sessions(
+    tx: &mut Transaction<'_, Sqlite>,
+    preset_id: i64,
+    sessions: &[Session],
+) -> Result<(), sqlx::Error> {
+    for (position, session) in sessions.iter().enumerate() {
+        insert_session(tx, preset_id, position as i64, session).await?;
+    }
+
+    return Ok(());
+}
+
+/// Store the preset's rule, or drop it when the preset is a plain session list
+async fn save_rule(
+    tx: &mut Transaction<'_, Sqlite>,
+    preset_id: i64,
+    rule: Option<&PresetRule>,
+) -> Result<(), sqlx::Error> {
+    let Some(rule) = rule else {
+        sqlx::query("DELETE FROM preset_rule WHERE preset_id = ?")
+            .bind(preset_id)
+            .execute(&mut **tx)
+            .await?;
+        return Ok(());
+    };
+
+    sqlx::query(
+        "INSERT INTO preset_rule (
+            preset_id, focus_in_sec, short_break_in_sec, long_break_in_sec,
+            long_break_every, focus_blocks
+         ) VALUES (?, ?, ?, ?, ?, ?)
+         ON CONFLICT(preset_id) DO UPDATE SET
+            focus_in_sec = excluded.focus_in_sec,
+            short_break_in_sec = excluded.short_break_in_sec,
+            long_break_in_sec = excluded.long_break_in_sec,
+            long_break_every = excluded.long_break_every,
+            focus_blocks = excluded.focus_blocks",
+    )
+    .bind(preset_id)
+    .bind(rule.focus.as_secs() as i64)
+    .bind(rule.short_break.as_secs() as i64)
+    .bind(rule.long_break.as_secs() as i64)
+    .bind(rule.long_break_every as i64)
+    .bind(rule.focus_blocks as i64)
+    .execute(&mut **tx)
+    .await?;
+
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Database;
+
+    async fn repository() -> PresetRepository {
+        let pool = Database::create_memory_pool().await.unwrap();
+        return PresetRepository::new(pool);
+    }
+
+    fn plain_preset() -> TimerPreset {
+        return TimerPreset {
+            title: "Plain".into(),
+            rule: None,
+            sessions: vec![
+                Session::new("Read".into(), Duration::from_secs(1500), SessionKind::WORK),
+                Session::new("Rest".into(), Duration::from_secs(300), SessionKind::BREAK),
+                Session::new("Write".into(), Duration::from_secs(2700), SessionKind::WORK),
+            ],
+            ..TimerPreset::default()
+        };
+    }
+
+    fn titles(preset: &TimerPreset) -> Vec<String> {
+        return preset
+            .sessions
+            .iter()
+            .map(|s| s.title.to_string())
+            .collect();
+    }
+
+    #[test]
+    fn create_then_get_keeps_everything() {
+        async_std::task::block_on(async {
+            let repository = repository().await;
+            let mut preset = plain_preset();
+            preset.repeat = PresetRepeat::Forever;
+            preset.pause_limits.max_pauses = Some(2);
+            preset.sessions[1].auto_start = Some(false);
+
+            let id = repository.create(&preset).await.unwrap();
+            let loaded = repository.get(id).await.unwrap().unwrap();
+
+            assert_eq!(loaded.id, Some(id));
+            assert_eq!(loaded.title.as_ref(), "Plain");
+            assert_eq!(titles(&loaded), ["Read", "Rest", "Write"]);
+            assert_eq!(loaded.sessions[2].duration, Duration::from_secs(2700));
+            assert_eq!(loaded.sessions[1].kind, SessionKind::BREAK);
+            assert_eq!(loaded.sessions[1].auto_start, Some(false));
+            assert_eq!(loaded.repeat, PresetRepeat::Forever);
+            assert_eq!(loaded.pause_limits.max_pauses, Some(2));
+            assert_eq!(loaded.rule, None);
+        });
+    }
+
+    #[test]
+    fn update_replaces_the_session_list_in_order() {
+        async_std::task::block_on(async {
+            let repository = repository().await;
+            let id = repository.create(&plain_preset()).await.unwrap();
+
+            let mut preset = repository.get(id).await.unwrap().unwrap();
+            preset.sessions.swap(0, 2);
+            preset.sessions.remove(1);
+            preset.sessions.push(Session::new(
+                "Review".into(),
+                Duration::from_secs(600),
+                SessionKind::WORK,
+            ));
+            repository.update(&preset).await.unwrap();
+
+            let loaded = repository.get(id).await.unwrap().unwrap();
+            assert_eq!(titles(&loaded), ["Write", "Read", "Review"]);
+
+            let listed = repository.list().await.unwrap();
+            assert_eq!(titles(&listed[0]), ["Write", "Read", "Review"]);
+
+            // the editor's list is written back with positions 0..n-1
+            let positions: Vec<(i64,)> = sqlx::query_as(
+                "SELECT position FROM session WHERE preset_id = ? ORDER BY position",
+            )
+            .bind(id)
+            .fetch_all(&repository.pool)
+            .await
+            .unwrap();
+            assert_eq!(positions, [(0,), (1,), (2,)]);
+        });
+    }
+
+    #[test]
+    fn rule_is_saved_and_dropped_with_the_preset() {
+        async_std::task::block_on(async {
+            let repository = repository().await;
+            let id = repository.create(&TimerPreset::default()).await.unwrap();
+
+            let mut preset = repository.get(id).await.unwrap().unwrap();
+            assert_eq!(preset.rule, Some(PresetRule::default()));
+            assert_eq!(preset.sessions.len(), PresetRule::default().expand().len());
+
+            preset.rule = None;
+            repository.update(&preset).await.unwrap();
+            assert_eq!(repository.get(id).await.unwrap().unwrap().rule, None);
+        });
+    }
+
+    #[test]
+    fn deleted_presets_are_hidden() {
+        async_std::task::block_on(async {
+            let repository = repository().await;
+            let kept = repository.create(&plain_preset()).await.unwrap();
+            let deleted = repository.create(&plain_preset()).await.unwrap();
+
+            repository.delete(deleted).await.unwrap();
+
+            let ids: Vec<_> = repository
+                .list()
+                .await
+                .unwrap()
+                .iter()
+                .map(|p| p.id)
+                .collect();
+            assert_eq!(ids, [Some(kept)]);
+            assert!(repository.get(deleted).await.unwrap().is_none());
+
+            let mut preset = plain_preset();
+            preset.id = Some(deleted);
+            assert!(repository.update(&preset).await.is_err());
+        });
+    }
+
+    #[test]
+    fn titles_include_deleted_presets() {
+        async_std::task::block_on(async {
+            let repository = repository().await;
+            let kept = repository.create(&plain_preset()).await.unwrap();
+            let deleted = repository.create(&plain_preset()).await.unwrap();
+
+            repository.delete(deleted).await.unwrap();
+
+            let ids: Vec<_> = repository
+                .titles()
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|(id, _)| id)
+                .collect();
+            assert_eq!(ids, [kept, deleted]);
+        });
+    }
+
+    #[test]
+    fn list_or_seed_creates_the_default_preset_once() {
+        async_std::task::block_on(async {
+            let repository = repository().await;
+
+            let seeded = repository.list_or_seed().await.unwrap();
+            assert_eq!(seeded.len(), 1);
+            assert_eq!(seeded[0].title, TimerPreset::default().title);
+
+            assert_eq!(repository.list_or_seed().await.unwrap().len(), 1);
+        });
+    }
+}
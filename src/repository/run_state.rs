@@ -0,0 +1,234 @@
+use std::time::Duration;
+
+use sqlx::SqlitePool;
+use sqlx::types::chrono::{DateTime, Utc};
+
+use crate::engine::timer::{PauseInterval, RunSnapshot};
+
+/// The preset run that was in progress when the app last saved it
+#[derive(Debug, Clone)]
+pub struct SavedRun {
+    pub preset_id: i64,
+    pub snapshot: RunSnapshot,
+    pub session_started_at: DateTime<Utc>,
+}
+
+#[derive(sqlx::FromRow)]
+struct RunStateRow {
+    preset_id: i64,
+    session_index: i64,
+    cycle: i64,
+    duration_in_ms: i64,
+    extended_in_ms: i64,
+    remaining_in_ms: i64,
+    deadline: Option<DateTime<Utc>>,
+    overtime_in_ms: i64,
+    last_flow_in_ms: Option<i64>,
+    session_started_at: DateTime<Utc>,
+}
+
+fn millis(value: i64) -> Duration {
+    return Duration::from_millis(value.max(0) as u64);
+}
+
+/// Keeps the live run in the `run_state` table so it can be resumed after a restart
+#[derive(Clone)]
+pub struct RunStateRepository {
+    pool: SqlitePool,
+}
+
+impl RunStateRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        return Self { pool };
+    }
+
+    pub async fn load(&self) -> Result<Option<SavedRun>, sqlx::Error> {
+        let row: Option<RunStateRow> = sqlx::query_as(
+            "SELECT preset_id, session_index, cycle, duration_in_ms, extended_in_ms,
+                    remaining_in_ms, deadline, overtime_in_ms, last_flow_in_ms, session_started_at
+             FROM run_state WHERE id = 1",
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let pauses: Vec<(DateTime<Utc>, i64)> =
+            sqlx::query_as("SELECT started_at, length_in_ms FROM run_state_pause ORDER BY id")
+                .fetch_all(&self.pool)
+                .await?;
+
+        return Ok(Some(SavedRun {
+            preset_id: row.preset_id,
+            snapshot: RunSnapshot {
+                session_index: row.session_index.max(0) as usize,
+                cycle: row.cycle.max(0) as u32,
+                duration: millis(row.duration_in_ms),
+                extended: millis(row.extended_in_ms),
+                remaining: millis(row.remaining_in_ms),
+                deadline: row.deadline.map(|deadline| deadline.into()),
+                overtime: millis(row.overtime_in_ms),
+                pauses: pauses
+                    .into_iter()
+                    .map(|(started_at, length)| PauseInterval {
+                        started_at: started_at.into(),
+                        length: millis(length),
+                    })
+                    .collect(),
+                last_flow: row.last_flow_in_ms.map(millis),
+            },
+            session_started_at: row.session_started_at,
+        }));
+    }
+
+    pub async fn save(&self, run: &SavedRun) -> Result<(), sqlx::Error> {
+        let snapshot = &run.snapshot;
+        let deadline: Option<DateTime<Utc>> = snapshot.deadline.map(|d| d.into());
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "INSERT INTO run_state (
+                id, preset_id, session_index, cycle, duration_in_ms, extended_in_ms,
+                remaining_in_ms, deadline, overtime_in_ms, last_flow_in_ms, session_started_at,
+                updated_at
+             ) VALUES (1, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+             ON CONFLICT(id) DO UPDATE SET
+                preset_id = excluded.preset_id,
+                session_index = excluded.session_index,
+                cycle = excluded.cycle,
+                duration_in_ms = excluded.duration_in_ms,
+                extended_in_ms = excluded.extended_in_ms,
+                remaining_in_ms = excluded.remaining_in_ms,
+                deadline = excluded.deadline,
+                overtime_in_ms = excluded.overtime_in_ms,
+                last_flow_in_ms = excluded.last_flow_in_ms,
+                session_started_at = excluded.session_started_at,
+                updated_at = excluded.updated_at",
+        )
+        .bind(run.preset_id)
+        .bind(snapshot.session_index as i64)
+        .bind(snapshot.cycle as i64)
+        .bind(snapshot.duration.as_millis() as i64)
+        .bind(snapshot.extended.as_millis() as i64)
+        .bind(snapshot.remaining.as_millis() as i64)
+        .bind(deadline)
+        .bind(snapshot.overtime.as_millis() as i64)
+        .bind(snapshot.last_flow.map(|flow| flow.as_millis() as i64))
+        .bind(run.session_started_at)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("DELETE FROM run_state_pause")
+            .execute(&mut *tx)
+            .await?;
+        for pause in &snapshot.pauses {
+            sqlx::query("INSERT INTO run_state_pause (started_at, length_in_ms) VALUES (?, ?)")
+                .bind(DateTime::<Utc>::from(pause.started_at))
+                .bind(pause.length.as_millis() as i64)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        return Ok(());
+    }
+
+    /// Forget the saved run, nothing is in progress anymore
+    pub async fn clear(&self) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        sqlx::query("DELETE FROM run_state")
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM run_state_pause")
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        return Ok(());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use super::*;
+    use crate::db::Database;
+    use crate::repository::presets::PresetRepository;
+    use crate::session::TimerPreset;
+
+    #[test]
+    fn save_then_load_then_clear() {
+        async_std::task::block_on(async {
+            let pool = Database::create_memory_pool().await.unwrap();
+            let preset_id = PresetRepository::new(pool.clone())
+                .create(&TimerPreset::default())
+                .await
+                .unwrap();
+            let repository = RunStateRepository::new(pool);
+            assert!(repository.load().await.unwrap().is_none());
+
+            let deadline = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+            let session_started_at = DateTime::from_timestamp(1_699_999_000, 0).unwrap();
+            let run = SavedRun {
+                preset_id,
+                snapshot: RunSnapshot {
+                    session_index: 2,
+                    cycle: 1,
+                    duration: Duration::from_secs(1500),
+                    extended: Duration::from_secs(300),
+                    remaining: Duration::from_millis(612_250),
+                    deadline: Some(deadline),
+                    overtime: Duration::ZERO,
+                    pauses: vec![PauseInterval {
+                        started_at: deadline - Duration::from_secs(900),
+                        length: Duration::from_millis(61_500),
+                    }],
+                    last_flow: Some(Duration::from_secs(2400)),
+                },
+                session_started_at,
+            };
+            repository.save(&run).await.unwrap();
+
+            let loaded = repository.load().await.unwrap().unwrap();
+            assert_eq!(loaded.preset_id, preset_id);
+            assert_eq!(loaded.snapshot.session_index, 2);
+            assert_eq!(loaded.snapshot.cycle, 1);
+            assert_eq!(loaded.snapshot.duration, Duration::from_secs(1500));
+            assert_eq!(loaded.snapshot.remaining, Duration::from_millis(612_250));
+            assert_eq!(loaded.snapshot.deadline, Some(deadline));
+            assert_eq!(loaded.snapshot.extended, Duration::from_secs(300));
+            assert_eq!(loaded.snapshot.last_flow, Some(Duration::from_secs(2400)));
+            assert_eq!(loaded.snapshot.pauses.len(), 1);
+            assert_eq!(
+                loaded.snapshot.pauses[0].started_at,
+                deadline - Duration::from_secs(900)
+            );
+            assert_eq!(
+                loaded.snapshot.pauses[0].length,
+                Duration::from_millis(61_500)
+            );
+            assert_eq!(loaded.session_started_at, session_started_at);
+
+            // saving again replaces the only row, a paused run has no deadline
+            let paused = SavedRun {
+                snapshot: RunSnapshot {
+                    deadline: None,
+                    overtime: Duration::from_secs(90),
+                    pauses: vec![],
+                    ..run.snapshot.clone()
+                },
+                ..run
+            };
+            repository.save(&paused).await.unwrap();
+            let loaded = repository.load().await.unwrap().unwrap();
+            assert_eq!(loaded.snapshot.deadline, None);
+            assert_eq!(loaded.snapshot.overtime, Duration::from_secs(90));
+            assert!(loaded.snapshot.pauses.is_empty());
+
+            repository.clear().await.unwrap();
+            assert!(repository.load().await.unwrap().is_none());
+        });
+    }
+}
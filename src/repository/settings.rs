@@ -0,0 +1,40 @@
+use sqlx::SqlitePool;
+
+use crate::settings::AppSettings;
+
+/// Reads and writes `AppSettings` from the `settings` table
+#[derive(Clone)]
+pub struct SettingsRepository {
+    pool: SqlitePool,
+}
+
+impl SettingsRepository {
+    pub fn new(pool: SqlitePool) -> Self {
+        return Self { pool };
+    }
+
+    pub async fn load(&self) -> Result<AppSettings, sqlx::Error> {
+        let rows: Vec<(String, String)> = sqlx::query_as("SELECT key, value FROM settings")
+            .fetch_all(&self.pool)
+            .await?;
+
+        return Ok(AppSettings::from_rows(&rows));
+    }
+
+    pub async fn save(&self, settings: &AppSettings) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        for (key, value) in settings.to_rows() {
+            sqlx::query(
+                "INSERT INTO settings (key, value) VALUES (?, ?)
+                 ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+            )
+            .bind(key)
+            .bind(value)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        return Ok(());
+    }
+}
@@ -7,8 +7,15 @@ use crate::constants::ONE_MIN_MS;
 // pomorodo session info
 #[derive(Debug, Clone)]
 pub struct TimerPreset {
+    pub id: Option<i64>, // None until the preset has been saved to the database
     pub title: SharedString,
+    pub description: Option<SharedString>,
     pub sessions: Vec<Session>,
+    pub repeat: PresetRepeat,
+    pub rule: Option<PresetRule>, // when set, `sessions` is generated from it
+    pub auto_start: AutoStart,
+    pub flow_breaks: FlowBreaks, // sizes the break after an open ended focus session
+    pub pause_limits: PauseLimits,
 }
 
 impl TimerPreset {
@@ -21,72 +28,430 @@ impl TimerPreset {
             .reduce(|acc, e| acc + e)
             .unwrap_or_else(|| Duration::from_micros(0));
     }
+
+    /// A one off preset counting down `duration` once, for the quick timer
+    pub fn quick_timer(duration: Duration) -> Self {
+        return Self::single(
+            "Quick timer",
+            Session::new("Quick timer".into(), duration, SessionKind::WORK),
+        );
+    }
+
+    /// A one off preset counting up until stopped
+    pub fn stopwatch() -> Self {
+        let mut session = Session::new("Stopwatch".into(), Duration::ZERO, SessionKind::WORK);
+        session.open_ended = true;
+        return Self::single("Stopwatch", session);
+    }
+
+    fn single(title: &'static str, session: Session) -> Self {
+        return Self {
+            id: None,
+            title: title.into(),
+            description: None,
+            sessions: vec![session],
+            repeat: PresetRepeat::default(),
+            rule: None,
+            auto_start: AutoStart::default(),
+            flow_breaks: FlowBreaks::default(),
+            pause_limits: PauseLimits::default(),
+        };
+    }
+
+    /// Whether `session` starts by itself once the one before it ends,
+    /// the session's own setting wins over the preset's
+    pub fn auto_starts(&self, session: &Session) -> bool {
+        return session.auto_start.unwrap_or(match session.kind {
+            SessionKind::WORK => self.auto_start.focus,
+            SessionKind::BREAK => self.auto_start.breaks,
+        });
+    }
 }
 
 impl Default for TimerPreset {
     fn default() -> Self {
+        let rule = PresetRule::default();
         return Self {
+            id: None,
             title: "Poromodo".into(),
-            sessions: vec![
-                Session::new(
-                    "Focus".into(),
-                    Duration::from_millis(ONE_MIN_MS * 60),
-                    SessionKind::WORK,
-                ),
-                Session::new(
-                    "Short break".into(),
-                    Duration::from_millis(ONE_MIN_MS * 10),
-                    SessionKind::BREAK,
-                ),
-                Session::new(
-                    "Focus".into(),
-                    Duration::from_millis(ONE_MIN_MS * 60),
-                    SessionKind::WORK,
-                ),
-                Session::new(
+            description: None,
+            repeat: PresetRepeat::default(),
+            sessions: rule.expand(),
+            rule: Some(rule),
+            auto_start: AutoStart::default(),
+            flow_breaks: FlowBreaks::default(),
+            pause_limits: PauseLimits::default(),
+        };
+    }
+}
+
+/// Which sessions of a preset start by themselves once the one before ends
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AutoStart {
+    pub focus: bool,
+    pub breaks: bool,
+    pub delay: Duration, // grace countdown before an automatic start
+}
+
+impl Default for AutoStart {
+    fn default() -> Self {
+        return Self {
+            focus: true,
+            breaks: true,
+            delay: Duration::ZERO,
+        };
+    }
+}
+
+/// Limits on pausing the sessions of a preset, None means no limit
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PauseLimits {
+    pub max_pauses: Option<u32>,         // pauses allowed per session
+    pub max_paused: Option<Duration>, // time a session can spend paused before it resumes by itself
+    pub abandon_after: Option<Duration>, // a focus session paused this long in one go is abandoned
+}
+
+/// How long a break gets after an open ended focus session that ran for a while
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FlowBreaks {
+    Ratio(u32),                          // a minute of break for every `n` minutes of focus
+    Brackets(Vec<(Duration, Duration)>), // (focus up to, break), the last one covers anything longer
+}
+
+impl Default for FlowBreaks {
+    fn default() -> Self {
+        let minutes = |m: u64| Duration::from_millis(ONE_MIN_MS * m);
+        return FlowBreaks::Brackets(vec![
+            (minutes(25), minutes(5)),
+            (minutes(50), minutes(8)),
+            (minutes(90), minutes(10)),
+        ]);
+    }
+}
+
+impl FlowBreaks {
+    pub fn break_for(&self, focus: Duration) -> Duration {
+        return match self {
+            FlowBreaks::Ratio(ratio) => {
+                let minutes = focus.as_secs() / 60 / (*ratio).max(1) as u64;
+                Duration::from_millis(ONE_MIN_MS * minutes.max(1))
+            }
+            FlowBreaks::Brackets(brackets) => brackets
+                .iter()
+                .find(|(up_to, _)| focus <= *up_to)
+                .or(brackets.last())
+                .map(|(_, length)| *length)
+                .unwrap_or(Duration::ZERO),
+        };
+    }
+
+    /// Read "/5" as a ratio or "25=5, 50=8" as brackets in minutes,
+    /// the form stored in the `flow_breaks` column of the `presets` table.
+    /// Every bracket needs a break of at least a minute
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        if let Some(ratio) = value.strip_prefix('/') {
+            let ratio = ratio.trim().parse::<u32>().ok().filter(|r| *r > 0)?;
+            return Some(FlowBreaks::Ratio(ratio));
+        }
+
+        let mut brackets = vec![];
+        for part in value.split(',') {
+            let (up_to, length) = part.split_once('=')?;
+            let up_to = up_to.trim().parse::<u64>().ok()?;
+            let length = length.trim().parse::<u64>().ok().filter(|l| *l > 0)?;
+            brackets.push((
+                Duration::from_millis(ONE_MIN_MS * up_to),
+                Duration::from_millis(ONE_MIN_MS * length),
+            ));
+        }
+
+        brackets.sort_by_key(|(up_to, _)| *up_to);
+        return Some(FlowBreaks::Brackets(brackets));
+    }
+
+    pub fn to_text(&self) -> String {
+        return match self {
+            FlowBreaks::Ratio(ratio) => format!("/{ratio}"),
+            FlowBreaks::Brackets(brackets) => brackets
+                .iter()
+                .map(|(up_to, length)| {
+                    format!("{}={}", up_to.as_secs() / 60, length.as_secs() / 60)
+                })
+                .collect::<Vec<_>>()
+                .join(", "),
+        };
+    }
+}
+
+/// Describes a preset as "N focus blocks with a long break every K" instead of a session list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PresetRule {
+    pub focus: Duration,
+    pub short_break: Duration,
+    pub long_break: Duration,
+    pub long_break_every: u32, // focus blocks between long breaks
+    pub focus_blocks: u32,
+}
+
+impl Default for PresetRule {
+    fn default() -> Self {
+        return Self {
+            focus: Duration::from_millis(ONE_MIN_MS * 60),
+            short_break: Duration::from_millis(ONE_MIN_MS * 10),
+            long_break: Duration::from_millis(ONE_MIN_MS * 20),
+            long_break_every: 2,
+            focus_blocks: 4,
+        };
+    }
+}
+
+impl PresetRule {
+    /// The sessions the rule stands for, a break between every two focus blocks
+    /// and none after the last one
+    pub fn expand(&self) -> Vec<Session> {
+        let every = self.long_break_every.max(1);
+        let mut sessions = vec![];
+        for block in 1..=self.focus_blocks {
+            sessions.push(Session::new("Focus".into(), self.focus, SessionKind::WORK));
+
+            if block == self.focus_blocks {
+                break;
+            }
+
+            if block % every == 0 {
+                sessions.push(Session::new(
                     "Long break".into(),
-                    Duration::from_millis(ONE_MIN_MS * 20),
+                    self.long_break,
                     SessionKind::BREAK,
-                ),
-                Session::new(
-                    "Focus".into(),
-                    Duration::from_millis(ONE_MIN_MS * 60),
-                    SessionKind::WORK,
-                ),
-                Session::new(
+                ));
+            } else {
+                sessions.push(Session::new(
                     "Short break".into(),
-                    Duration::from_millis(ONE_MIN_MS * 10),
+                    self.short_break,
                     SessionKind::BREAK,
-                ),
-                Session::new(
-                    "Focus".into(),
-                    Duration::from_millis(ONE_MIN_MS * 60),
-                    SessionKind::WORK,
-                ),
-            ],
+                ));
+            }
+        }
+
+        return sessions;
+    }
+}
+
+/// How many times a preset runs through its sessions before it completes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PresetRepeat {
+    Times(u32),
+    Forever, // loops until stopped
+}
+
+impl Default for PresetRepeat {
+    fn default() -> Self {
+        return PresetRepeat::Times(1);
+    }
+}
+
+impl PresetRepeat {
+    /// The value stored in the `repeat_count` column of the `presets` table, 0 loops forever
+    pub fn as_count(&self) -> i64 {
+        return match self {
+            PresetRepeat::Times(times) => *times as i64,
+            PresetRepeat::Forever => 0,
+        };
+    }
+
+    pub fn from_count(count: i64) -> Self {
+        if count <= 0 {
+            return PresetRepeat::Forever;
+        }
+        return PresetRepeat::Times(count.min(u32::MAX as i64) as u32);
+    }
+
+    /// Whether another pass should start after the (0 based) `cycle` one
+    pub fn has_cycle_after(&self, cycle: u32) -> bool {
+        return match self {
+            PresetRepeat::Times(times) => cycle + 1 < *times,
+            PresetRepeat::Forever => true,
         };
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SessionKind {
     WORK,
     BREAK,
 }
 
-#[derive(Debug, Clone)]
+impl SessionKind {
+    /// The value stored in the `type` column of the `session` table
+    pub fn as_str(&self) -> &'static str {
+        return match self {
+            SessionKind::WORK => "focus",
+            SessionKind::BREAK => "break",
+        };
+    }
+
+    pub fn from_str(value: &str) -> Option<Self> {
+        return match value {
+            "focus" => Some(SessionKind::WORK),
+            "break" => Some(SessionKind::BREAK),
+            _ => None,
+        };
+    }
+}
+
+/// Colors a session can be given in the preset editor
+pub const SESSION_COLORS: [u32; 6] = [0x5c6bc0, 0x4db6ac, 0xe57373, 0xffb74d, 0x9575cd, 0x81c784];
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct Session {
     pub title: SharedString,
     pub duration: Duration,
     pub kind: SessionKind,
+    pub color: u32,               // 0xRRGGBB, used by the timeline
+    pub auto_start: Option<bool>, // None follows the preset's `AutoStart`
+    pub open_ended: bool,         // counts up until ended by hand, `duration` is ignored
 }
 
 impl Session {
     pub fn new(title: SharedString, duration: Duration, kind: SessionKind) -> Self {
+        let color = Session::default_color(&kind);
         return Session {
             title,
             duration,
             kind,
+            color,
+            auto_start: None,
+            open_ended: false,
         };
     }
+
+    pub fn default_color(kind: &SessionKind) -> u32 {
+        return match kind {
+            SessionKind::WORK => SESSION_COLORS[0],
+            SessionKind::BREAK => SESSION_COLORS[1],
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIN: Duration = Duration::from_secs(60);
+
+    fn outline(sessions: &[Session]) -> Vec<(String, Duration)> {
+        return sessions
+            .iter()
+            .map(|s| (s.title.to_string(), s.duration))
+            .collect();
+    }
+
+    #[test]
+    fn default_rule_expands_to_the_original_preset() {
+        let focus = ("Focus".to_string(), 60 * MIN);
+        let short = ("Short break".to_string(), 10 * MIN);
+        let long = ("Long break".to_string(), 20 * MIN);
+
+        let sessions = PresetRule::default().expand();
+        assert_eq!(
+            outline(&sessions),
+            [
+                focus.clone(),
+                short.clone(),
+                focus.clone(),
+                long,
+                focus.clone(),
+                short,
+                focus,
+            ]
+        );
+        assert_eq!(sessions[0].kind, SessionKind::WORK);
+        assert_eq!(sessions[3].kind, SessionKind::BREAK);
+    }
+
+    #[test]
+    fn long_break_every_n_blocks_when_n_does_not_divide_them() {
+        let rule = PresetRule {
+            long_break_every: 3,
+            focus_blocks: 7,
+            ..PresetRule::default()
+        };
+
+        let breaks: Vec<String> = rule
+            .expand()
+            .iter()
+            .filter(|s| s.kind == SessionKind::BREAK)
+            .map(|s| s.title.to_string())
+            .collect();
+        assert_eq!(
+            breaks,
+            [
+                "Short break",
+                "Short break",
+                "Long break",
+                "Short break",
+                "Short break",
+                "Long break",
+            ]
+        );
+        assert_eq!(rule.expand().len(), 13);
+    }
+
+    #[test]
+    fn flow_breaks_round_trip_through_text() {
+        for text in ["/5", "25=5, 50=8, 90=10"] {
+            let parsed = FlowBreaks::parse(text).unwrap();
+            assert_eq!(parsed.to_text(), text);
+            assert_eq!(FlowBreaks::parse(&parsed.to_text()), Some(parsed));
+        }
+
+        assert_eq!(
+            FlowBreaks::parse(&FlowBreaks::default().to_text()),
+            Some(FlowBreaks::default())
+        );
+        // brackets are kept in order of how much focus they cover
+        assert_eq!(
+            FlowBreaks::parse(" 50 = 8,25=5 ").unwrap().to_text(),
+            "25=5, 50=8"
+        );
+    }
+
+    #[test]
+    fn flow_breaks_reject_empty_and_zero_length_breaks() {
+        for text in [
+            "",
+            " ",
+            "/",
+            "/0",
+            "25=0",
+            "25=5, 50=0",
+            "25=5,",
+            "25",
+            "a=5",
+        ] {
+            assert_eq!(FlowBreaks::parse(text), None, "{text:?}");
+        }
+    }
+
+    #[test]
+    fn flow_break_brackets_include_their_upper_bound() {
+        let breaks = FlowBreaks::default();
+
+        assert_eq!(breaks.break_for(10 * MIN), 5 * MIN);
+        assert_eq!(breaks.break_for(25 * MIN), 5 * MIN);
+        assert_eq!(breaks.break_for(25 * MIN + Duration::from_secs(1)), 8 * MIN);
+        assert_eq!(breaks.break_for(90 * MIN), 10 * MIN);
+        // the last bracket covers anything longer
+        assert_eq!(breaks.break_for(300 * MIN), 10 * MIN);
+    }
+
+    #[test]
+    fn flow_break_ratio_gives_at_least_a_minute() {
+        let breaks = FlowBreaks::Ratio(5);
+
+        assert_eq!(breaks.break_for(50 * MIN), 10 * MIN);
+        assert_eq!(breaks.break_for(54 * MIN), 10 * MIN);
+        assert_eq!(breaks.break_for(2 * MIN), MIN);
+    }
 }
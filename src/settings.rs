@@ -0,0 +1,130 @@
+use gpui::{App, AppContext, Global};
+
+use crate::db::Database;
+use crate::duration::ClockFormat;
+use crate::engine::timer::SuspendPolicy;
+use crate::statistics::DailyGoal;
+
+/// App wide preferences, stored as key/value rows in the `settings` table
+#[derive(Debug, Clone)]
+pub struct AppSettings {
+    pub suspend_policy: SuspendPolicy,
+    pub extend_increments: Vec<u64>, // minutes offered by the "more time" buttons
+    pub confirm_abandon: bool,       // ask before stopping a focus session that is under way
+    pub overtime: bool,              // count up past zero instead of moving on
+    pub log_adhoc: bool,             // keep quick timers and stopwatches in the history
+    pub clock_format: ClockFormat,
+    pub show_percent: bool, // show the share of the session left next to the clock
+    pub daily_goal: DailyGoal,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        return Self {
+            suspend_policy: SuspendPolicy::default(),
+            extend_increments: vec![1, 5],
+            confirm_abandon: true,
+            overtime: false,
+            log_adhoc: false,
+            clock_format: ClockFormat::default(),
+            show_percent: false,
+            daily_goal: DailyGoal::default(),
+        };
+    }
+}
+
+impl Global for AppSettings {}
+
+impl AppSettings {
+    /// Change the global settings and save them in the background
+    pub fn update(cx: &mut App, f: impl FnOnce(&mut AppSettings)) {
+        let settings = cx.global_mut::<AppSettings>();
+        f(settings);
+
+        let settings = settings.clone();
+        let repository = cx.global::<Database>().settings();
+        cx.background_spawn(async move { repository.save(&settings).await })
+            .detach_and_log_err(cx);
+    }
+
+    /// Build the settings from stored rows, unknown keys and bad values fall back to defaults
+    pub fn from_rows(rows: &[(String, String)]) -> Self {
+        let mut settings = AppSettings::default();
+        for (key, value) in rows {
+            match key.as_str() {
+                "suspend_policy" => {
+                    if let Some(policy) = SuspendPolicy::from_str(value) {
+                        settings.suspend_policy = policy;
+                    }
+                }
+                "confirm_abandon" => settings.confirm_abandon = value == "1",
+                "overtime" => settings.overtime = value == "1",
+                "log_adhoc" => settings.log_adhoc = value == "1",
+                "clock_format" => {
+                    if let Some(format) = ClockFormat::from_str(value) {
+                        settings.clock_format = format;
+                    }
+                }
+                "show_percent" => settings.show_percent = value == "1",
+                "daily_goal" => {
+                    if let Some(goal) = DailyGoal::parse(value) {
+                        settings.daily_goal = goal;
+                    }
+                }
+                "extend_increments" => {
+                    if let Some(increments) = AppSettings::parse_increments(value) {
+                        settings.extend_increments = increments;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        return settings;
+    }
+
+    pub fn to_rows(&self) -> Vec<(&'static str, String)> {
+        return vec![
+            ("suspend_policy", self.suspend_policy.as_str().to_string()),
+            ("extend_increments", self.increments_text()),
+            ("confirm_abandon", bool_value(self.confirm_abandon)),
+            ("overtime", bool_value(self.overtime)),
+            ("log_adhoc", bool_value(self.log_adhoc)),
+            ("clock_format", self.clock_format.as_str().to_string()),
+            ("show_percent", bool_value(self.show_percent)),
+            ("daily_goal", self.daily_goal.to_text()),
+        ];
+    }
+
+    /// Parse a comma separated list of minutes, None unless every entry is a positive number
+    pub fn parse_increments(value: &str) -> Option<Vec<u64>> {
+        let increments = value
+            .split(',')
+            .map(|part| {
+                part.trim()
+                    .parse::<u64>()
+                    .ok()
+                    .filter(|minutes| *minutes > 0)
+            })
+            .collect::<Option<Vec<u64>>>()?;
+
+        if increments.is_empty() {
+            return None;
+        }
+        return Some(increments);
+    }
+
+    /// The increments the way `parse_increments` reads them back
+    pub fn increments_text(&self) -> String {
+        return self
+            .extend_increments
+            .iter()
+            .map(|minutes| minutes.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+    }
+}
+
+fn bool_value(value: bool) -> String {
+    return if value { "1" } else { "0" }.to_string();
+}
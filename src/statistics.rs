@@ -0,0 +1,276 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Datelike, Days, Local, NaiveDate, Utc};
+
+use crate::history::{HistoryEntry, InterruptionKind, SessionOutcome};
+use crate::session::SessionKind;
+
+/// Totals over the history entries of some stretch of time
+#[derive(Debug, Clone, Default)]
+pub struct PeriodStats {
+    pub focus_time: Duration,
+    pub break_time: Duration,
+    pub focus_sessions: usize,
+    pub completed: usize,
+    pub abandoned: usize, // stopped by hand or left paused too long
+    pub internal_interruptions: usize,
+    pub external_interruptions: usize,
+}
+
+impl PeriodStats {
+    pub fn from_entries<'a>(entries: impl IntoIterator<Item = &'a HistoryEntry>) -> Self {
+        let mut stats = PeriodStats::default();
+        // quick runs are logged on their own, they don't add to anything here
+        for entry in entries.into_iter().filter(|entry| !entry.adhoc) {
+            match entry.kind {
+                SessionKind::WORK => {
+                    stats.focus_time += entry.elapsed;
+                    stats.focus_sessions += 1;
+                }
+                SessionKind::BREAK => {
+                    stats.break_time += entry.elapsed;
+                    continue;
+                }
+            }
+
+            // only focus sessions count as completed or abandoned
+            match entry.outcome {
+                SessionOutcome::Completed => stats.completed += 1,
+                SessionOutcome::Stopped | SessionOutcome::Abandoned => stats.abandoned += 1,
+                SessionOutcome::Skipped => {}
+            }
+
+            for interruption in &entry.interruptions {
+                match interruption.kind {
+                    InterruptionKind::Internal => stats.internal_interruptions += 1,
+                    InterruptionKind::External => stats.external_interruptions += 1,
+                }
+            }
+        }
+
+        return stats;
+    }
+
+    /// How long a focus session ran on average, whatever its outcome
+    pub fn average_focus(&self) -> Duration {
+        if self.focus_sessions == 0 {
+            return Duration::ZERO;
+        }
+        return self.focus_time / self.focus_sessions as u32;
+    }
+}
+
+/// Focus done on one local day
+#[derive(Debug, Clone, Copy)]
+pub struct DayFocus {
+    pub day: NaiveDate,
+    pub focus: Duration,
+    pub sessions: u32,
+    pub completed: u32, // focus sessions that ran to the end
+}
+
+impl DayFocus {
+    pub fn empty(day: NaiveDate) -> Self {
+        return Self {
+            day,
+            focus: Duration::ZERO,
+            sessions: 0,
+            completed: 0,
+        };
+    }
+}
+
+/// A daily focus target, either in minutes of focus or in completed focus sessions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DailyGoal {
+    #[default]
+    Off,
+    Minutes(u32),
+    Sessions(u32),
+}
+
+impl DailyGoal {
+    /// The value stored in the `settings` and `daily_goal` tables, like "minutes:120"
+    pub fn to_text(self) -> String {
+        return match self {
+            DailyGoal::Off => "off".to_string(),
+            DailyGoal::Minutes(minutes) => format!("minutes:{minutes}"),
+            DailyGoal::Sessions(sessions) => format!("sessions:{sessions}"),
+        };
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        if value == "off" {
+            return Some(DailyGoal::Off);
+        }
+
+        let (kind, target) = value.split_once(':')?;
+        let target = target.parse::<u32>().ok().filter(|t| *t > 0)?;
+        return match kind {
+            "minutes" => Some(DailyGoal::Minutes(target)),
+            "sessions" => Some(DailyGoal::Sessions(target)),
+            _ => None,
+        };
+    }
+
+    /// How far a day got towards the goal as (done, target), None without a goal
+    pub fn progress(&self, focus: Duration, completed: u32) -> Option<(u32, u32)> {
+        return match self {
+            DailyGoal::Off => None,
+            DailyGoal::Minutes(target) => Some(((focus.as_secs() / 60) as u32, *target)),
+            DailyGoal::Sessions(target) => Some((completed, *target)),
+        };
+    }
+
+    pub fn is_met(&self, focus: Duration, completed: u32) -> bool {
+        return self
+            .progress(focus, completed)
+            .is_some_and(|(done, target)| done >= target);
+    }
+}
+
+/// The goal that applied on `day`, the latest one set on or before it.
+/// `goals` is the goal history, oldest first
+pub fn goal_on(day: NaiveDate, goals: &[(NaiveDate, DailyGoal)]) -> DailyGoal {
+    return goals
+        .iter()
+        .take_while(|(since, _)| *since <= day)
+        .last()
+        .map(|(_, goal)| *goal)
+        .unwrap_or_default();
+}
+
+/// The local day an entry started on
+pub fn local_day(at: DateTime<Utc>) -> NaiveDate {
+    return at.with_timezone(&Local).date_naive();
+}
+
+/// The start of `day` in local time
+pub fn start_of_day(day: NaiveDate) -> DateTime<Utc> {
+    let midnight = day.and_hms_opt(0, 0, 0).expect("midnight is a valid time");
+    return match midnight.and_local_timezone(Local).earliest() {
+        Some(at) => at.with_timezone(&Utc),
+        // a day that starts inside a DST gap, UTC midnight is close enough
+        None => midnight.and_utc(),
+    };
+}
+
+/// Monday of the week `day` is in
+pub fn start_of_week(day: NaiveDate) -> NaiveDate {
+    let since_monday = day.weekday().num_days_from_monday() as u64;
+    return day - Days::new(since_monday);
+}
+
+pub fn start_of_month(day: NaiveDate) -> NaiveDate {
+    return day.with_day(1).expect("every month has a first day");
+}
+
+/// Focus on each of the `days` days up to and including `last`, oldest first
+pub fn daily_focus(entries: &[HistoryEntry], last: NaiveDate, days: u64) -> Vec<DayFocus> {
+    let first = last - Days::new(days.saturating_sub(1));
+    return first
+        .iter_days()
+        .take(days as usize)
+        .map(|day| {
+            let mut focus = DayFocus::empty(day);
+            for entry in entries
+                .iter()
+                .filter(|entry| entry.is_focus() && local_day(entry.started_at) == day)
+            {
+                focus.focus += entry.elapsed;
+                focus.sessions += 1;
+                if entry.outcome == SessionOutcome::Completed {
+                    focus.completed += 1;
+                }
+            }
+            focus
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(kind: SessionKind, outcome: SessionOutcome, adhoc: bool) -> HistoryEntry {
+        let elapsed = Duration::from_secs(600);
+        return HistoryEntry {
+            id: None,
+            preset_id: None,
+            session_title: "Session".into(),
+            kind,
+            planned: elapsed,
+            extended: Duration::ZERO,
+            elapsed,
+            overtime: Duration::ZERO,
+            paused: Duration::ZERO,
+            pauses: vec![],
+            interruptions: vec![],
+            started_at: Utc::now(),
+            ended_at: Utc::now(),
+            outcome,
+            adhoc,
+        };
+    }
+
+    #[test]
+    fn period_stats_count_outcomes_of_focus_sessions_only() {
+        let entries = [
+            entry(SessionKind::WORK, SessionOutcome::Completed, false),
+            entry(SessionKind::WORK, SessionOutcome::Stopped, false),
+            entry(SessionKind::BREAK, SessionOutcome::Completed, false),
+            entry(SessionKind::BREAK, SessionOutcome::Abandoned, false),
+            entry(SessionKind::WORK, SessionOutcome::Completed, true),
+        ];
+
+        let stats = PeriodStats::from_entries(&entries);
+        assert_eq!(stats.focus_sessions, 2);
+        assert_eq!(stats.completed, 1);
+        assert_eq!(stats.abandoned, 1);
+        assert_eq!(stats.focus_time, Duration::from_secs(1200));
+        assert_eq!(stats.break_time, Duration::from_secs(1200));
+    }
+
+    fn date(day: u32) -> NaiveDate {
+        return NaiveDate::from_ymd_opt(2026, 3, day).unwrap();
+    }
+
+    #[test]
+    fn goal_on_picks_the_latest_goal_set_on_or_before_the_day() {
+        let goals = [
+            (date(3), DailyGoal::Minutes(60)),
+            (date(10), DailyGoal::Sessions(4)),
+            (date(20), DailyGoal::Off),
+        ];
+
+        assert_eq!(goal_on(date(1), &goals), DailyGoal::Off);
+        assert_eq!(goal_on(date(3), &goals), DailyGoal::Minutes(60));
+        assert_eq!(goal_on(date(9), &goals), DailyGoal::Minutes(60));
+        assert_eq!(goal_on(date(10), &goals), DailyGoal::Sessions(4));
+        assert_eq!(goal_on(date(25), &goals), DailyGoal::Off);
+        assert_eq!(goal_on(date(25), &[]), DailyGoal::Off);
+    }
+
+    #[test]
+    fn daily_goal_round_trips_through_text() {
+        for goal in [
+            DailyGoal::Off,
+            DailyGoal::Minutes(120),
+            DailyGoal::Sessions(4),
+        ] {
+            assert_eq!(DailyGoal::parse(&goal.to_text()), Some(goal));
+        }
+
+        assert_eq!(DailyGoal::Minutes(120).to_text(), "minutes:120");
+        for text in [
+            "",
+            "minutes",
+            "minutes:0",
+            "minutes:-5",
+            "hours:2",
+            "sessions:x",
+        ] {
+            assert_eq!(DailyGoal::parse(text), None, "{text:?}");
+        }
+    }
+}